@@ -0,0 +1,370 @@
+//! Procedural macros for [mcnbt](https://docs.rs/mcnbt).
+//!
+//! Use them through the `derive` feature of `mcnbt` rather than depending on
+//! this crate directly.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, ExprPath, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Type,
+};
+
+mod snbt;
+
+/// Maps the fields of a struct onto the entries of a `TAG_Compound`.
+///
+/// Implements `mcnbt::convert::FromTag`, `mcnbt::convert::IntoTag`,
+/// `TryFrom<&mcnbt::Tag>` and `From<Self> for mcnbt::Tag`.
+///
+/// Every field has to implement `FromTag` and `IntoTag`. Fields of type
+/// `Option` may be absent. Unknown entries are skipped.
+///
+/// # Container attributes
+///
+/// - `#[nbt(deny_unknown_keys)]`: fail on entries without a matching field
+///
+/// # Field attributes
+///
+/// - `#[nbt(rename = "Name")]`: use a different name for the entry
+/// - `#[nbt(byte_array)]`, `#[nbt(int_array)]`, `#[nbt(long_array)]`: store
+///   a `Vec<i8>`, `Vec<i32>` or `Vec<i64>` as an array instead of a list
+/// - `#[nbt(default)]`, `#[nbt(default = "path")]`: use `Default::default()`
+///   or the given function if the entry is absent
+/// - `#[nbt(flatten)]`: read and write the entries of the field from and into
+///   the same compound
+/// - `#[nbt(skip)]`: neither read nor write the field and use its default
+///   value
+#[proc_macro_derive(NbtCompound, attributes(nbt))]
+pub fn derive_nbt_compound(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Creates a [`Tag`] from SNBT which is parsed at compile time.
+///
+/// The tag itself is unnamed. Numbers take the suffixes `b`, `s`, `l`, `f`
+/// and `d`; numbers without a suffix are `TAG_Int`s or `TAG_Double`s. Keys
+/// and strings may be unquoted if they are identifiers and `true` and
+/// `false` are `TAG_Byte`s. Only double quotes are supported for strings.
+/// As `0b` is not a valid token in Rust, write `0_b` or `false` instead.
+///
+/// Rust values implementing `IntoTag` are interpolated with `#name` or
+/// `#(expression)`, both as values and as keys. The types of interpolated
+/// list elements are not checked.
+///
+/// [`Tag`]: https://docs.rs/mcnbt/latest/mcnbt/tag/enum.Tag.html
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, double, list, snbt, string};
+///
+/// assert_eq!(
+///     snbt!({ id: "minecraft:stone", Count: 1b, Pos: [0.5d, 64d, 0.5d] }),
+///     compound!(
+///         string!("id" => "minecraft:stone"),
+///         byte!("Count" => 1),
+///         list!("Pos" => double!(0.5), double!(64.0), double!(0.5)),
+///     )
+/// );
+///
+/// let count = 16i8;
+/// let tag = snbt!({ Count: #count, Tags: [#("a".repeat(2)), b], Ints: [I; 1, 2] });
+/// assert_eq!(tag["Count"].as_byte(), Some(16));
+/// assert_eq!(tag["Tags"][0].as_str(), Some("aa"));
+/// assert_eq!(tag["Ints"].as_int_array(), Some(&[1, 2][..]));
+/// ```
+///
+/// Syntax errors and values out of range fail to compile.
+///
+/// ```rust,compile_fail
+/// mcnbt::snbt!({ Count: 128b });
+/// ```
+///
+/// ```rust,compile_fail
+/// mcnbt::snbt!([1b, 2s]);
+/// ```
+#[proc_macro]
+pub fn snbt(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    snbt::expand(input.into())
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    deny_unknown_keys: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Array {
+    Byte,
+    Int,
+    Long,
+}
+
+enum DefaultValue {
+    Trait,
+    Function(ExprPath),
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    array: Option<Array>,
+    default: Option<DefaultValue>,
+    flatten: bool,
+    skip: bool,
+}
+
+fn container_attrs(input: &DeriveInput) -> syn::Result<ContainerAttrs> {
+    let mut attrs = ContainerAttrs::default();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("nbt"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("deny_unknown_keys") {
+                attrs.deny_unknown_keys = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown container attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("nbt"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let array = if meta.path.is_ident("byte_array") {
+                Some(Array::Byte)
+            } else if meta.path.is_ident("int_array") {
+                Some(Array::Int)
+            } else if meta.path.is_ident("long_array") {
+                Some(Array::Long)
+            } else {
+                None
+            };
+
+            if let Some(array) = array {
+                if attrs.array.is_some() {
+                    return Err(meta.error("only one array type may be specified"));
+                }
+                attrs.array = Some(array);
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                    DefaultValue::Function(meta.value()?.parse::<LitStr>()?.parse()?)
+                } else {
+                    DefaultValue::Trait
+                });
+            } else if meta.path.is_ident("flatten") {
+                attrs.flatten = true;
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else {
+                return Err(meta.error("unknown field attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if attrs.flatten && (attrs.rename.is_some() || attrs.array.is_some()) {
+        return Err(Error::new(
+            field.span(),
+            "`flatten` cannot be combined with `rename` or an array type",
+        ));
+    }
+    Ok(attrs)
+}
+
+/// Returns `T` if the type is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let private = quote!(::mcnbt::convert::__private);
+    let container = container_attrs(&input)?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "NbtCompound can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.ident.span(),
+                "NbtCompound can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut reads = vec![];
+    let mut writes = vec![];
+    let mut keys = vec![];
+    let mut flattened = false;
+
+    for field in fields {
+        let attrs = field_attrs(field)?;
+        let ident = field.ident.as_ref().unwrap();
+
+        if attrs.skip {
+            reads.push(quote!(#ident: #private::Default::default()));
+            continue;
+        }
+
+        if attrs.flatten {
+            flattened = true;
+            reads.push(quote!(#ident: ::mcnbt::convert::FromTag::from_tag(tag)?));
+            writes.push(quote! {
+                tags.extend(#private::flatten(::mcnbt::convert::IntoTag::into_tag(self.#ident)));
+            });
+            continue;
+        }
+
+        let key = attrs.rename.unwrap_or_else(|| ident.to_string());
+        keys.push(key.clone());
+
+        let optional = option_inner(&field.ty);
+        let (from, into) = match attrs.array {
+            Some(Array::Byte) => (
+                quote!(#private::from_byte_array(child)?),
+                quote!(#private::into_byte_array(value)),
+            ),
+            Some(Array::Int) => (
+                quote!(#private::from_int_array(child)?),
+                quote!(#private::into_int_array(value)),
+            ),
+            Some(Array::Long) => (
+                quote!(#private::from_long_array(child)?),
+                quote!(#private::into_long_array(value)),
+            ),
+            None => {
+                let ty = optional.unwrap_or(&field.ty);
+                (
+                    quote!(<#ty as ::mcnbt::convert::FromTag>::from_tag(child)?),
+                    quote!(::mcnbt::convert::IntoTag::into_tag(value)),
+                )
+            }
+        };
+
+        let absent = match (&attrs.default, optional) {
+            (Some(DefaultValue::Trait), _) => quote!(#private::Default::default()),
+            (Some(DefaultValue::Function(path)), _) => quote!(#path()),
+            (None, Some(_)) => quote!(#private::None),
+            (None, None) => quote!(return #private::Err(#private::missing(#key))),
+        };
+        let present = match optional {
+            Some(_) => quote!(#private::Some(#from)),
+            None => from,
+        };
+        reads.push(quote! {
+            #ident: match tag.get(#key) {
+                #private::Some(child) => #present,
+                #private::None => #absent,
+            }
+        });
+
+        writes.push(match optional {
+            Some(_) => quote! {
+                if let #private::Some(value) = self.#ident {
+                    tags.push(#private::named(#into, #key));
+                }
+            },
+            None => quote! {
+                let value = self.#ident;
+                tags.push(#private::named(#into, #key));
+            },
+        });
+    }
+
+    let deny_unknown_keys = if container.deny_unknown_keys {
+        if flattened {
+            return Err(Error::new(
+                input.ident.span(),
+                "`deny_unknown_keys` cannot be combined with `flatten`",
+            ));
+        }
+        quote! {
+            let keys: &[&str] = &[#(#keys),*];
+            for child in #private::compound(tag)? {
+                if !keys.contains(&child.tag_name().unwrap_or_default()) {
+                    return #private::Err(#private::unknown(child));
+                }
+            }
+        }
+    } else {
+        quote!(#private::compound(tag)?;)
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::mcnbt::convert::FromTag for #ident #ty_generics #where_clause {
+            fn from_tag(tag: &::mcnbt::Tag) -> #private::Result<Self, ::mcnbt::errors::Error> {
+                #deny_unknown_keys
+                #private::Ok(Self {
+                    #(#reads,)*
+                })
+            }
+        }
+
+        impl #impl_generics ::mcnbt::convert::IntoTag for #ident #ty_generics #where_clause {
+            fn into_tag(self) -> ::mcnbt::Tag {
+                let mut tags = #private::Vec::new();
+                #(#writes)*
+                ::mcnbt::Tag::Compound(#private::None, tags)
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<&::mcnbt::Tag> for #ident #ty_generics #where_clause {
+            type Error = ::mcnbt::errors::Error;
+
+            fn try_from(tag: &::mcnbt::Tag) -> #private::Result<Self, Self::Error> {
+                ::mcnbt::convert::FromTag::from_tag(tag)
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#ident #ty_generics> for ::mcnbt::Tag #where_clause {
+            fn from(value: #ident #ty_generics) -> Self {
+                ::mcnbt::convert::IntoTag::into_tag(value)
+            }
+        }
+    })
+}
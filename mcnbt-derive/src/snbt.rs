@@ -0,0 +1,401 @@
+//! Parser for the `snbt!` macro.
+
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, bracketed, parenthesized, Error, Ident, Lit, LitStr, Token};
+
+/// A key of a compound.
+enum Key {
+    Literal(String, Span),
+    Interpolated(TokenStream),
+}
+
+/// An SNBT value.
+enum Value {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    ByteArray(Vec<Element>),
+    IntArray(Vec<Element>),
+    LongArray(Vec<Element>),
+    List(Vec<Value>),
+    Compound(Vec<(Key, Value)>),
+    Interpolated(TokenStream),
+}
+
+/// An element of an array.
+enum Element {
+    Literal(Literal),
+    Interpolated(TokenStream),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Array {
+    Byte,
+    Int,
+    Long,
+}
+
+impl Value {
+    /// Returns the name of the tag type or `None` if it is only known at
+    /// runtime.
+    fn type_name(&self) -> Option<&'static str> {
+        Some(match self {
+            Value::Byte(_) => "TAG_Byte",
+            Value::Short(_) => "TAG_Short",
+            Value::Int(_) => "TAG_Int",
+            Value::Long(_) => "TAG_Long",
+            Value::Float(_) => "TAG_Float",
+            Value::Double(_) => "TAG_Double",
+            Value::String(_) => "TAG_String",
+            Value::ByteArray(_) => "TAG_Byte_Array",
+            Value::IntArray(_) => "TAG_Int_Array",
+            Value::LongArray(_) => "TAG_Long_Array",
+            Value::List(_) => "TAG_List",
+            Value::Compound(_) => "TAG_Compound",
+            Value::Interpolated(_) => return None,
+        })
+    }
+
+    /// Generates the expression creating the tag with the given name.
+    fn expand(&self, name: TokenStream) -> TokenStream {
+        match self {
+            Value::Byte(value) => quote!(::mcnbt::Tag::Byte(#name, #value)),
+            Value::Short(value) => quote!(::mcnbt::Tag::Short(#name, #value)),
+            Value::Int(value) => quote!(::mcnbt::Tag::Int(#name, #value)),
+            Value::Long(value) => quote!(::mcnbt::Tag::Long(#name, #value)),
+            Value::Float(value) => quote!(::mcnbt::Tag::Float(#name, #value)),
+            Value::Double(value) => quote!(::mcnbt::Tag::Double(#name, #value)),
+            Value::String(value) => {
+                quote!(::mcnbt::Tag::String(#name, ::std::string::String::from(#value)))
+            }
+            Value::ByteArray(elements) => {
+                quote!(::mcnbt::Tag::ByteArray(#name, ::std::vec![#(#elements),*]))
+            }
+            Value::IntArray(elements) => {
+                quote!(::mcnbt::Tag::IntArray(#name, ::std::vec![#(#elements),*]))
+            }
+            Value::LongArray(elements) => {
+                quote!(::mcnbt::Tag::LongArray(#name, ::std::vec![#(#elements),*]))
+            }
+            Value::List(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.expand(quote!(::std::option::Option::None)));
+                quote!(::mcnbt::Tag::List(#name, ::std::vec![#(#values),*]))
+            }
+            Value::Compound(entries) => {
+                let entries = entries.iter().map(|(key, value)| {
+                    let key = match key {
+                        Key::Literal(key, _) => key.to_token_stream(),
+                        Key::Interpolated(key) => {
+                            quote!(::std::convert::AsRef::<str>::as_ref(&#key))
+                        }
+                    };
+                    value.expand(quote!(::std::option::Option::Some(
+                        ::std::string::String::from(#key)
+                    )))
+                });
+                quote!(::mcnbt::Tag::Compound(#name, ::std::vec![#(#entries),*]))
+            }
+            Value::Interpolated(expr) => {
+                let tag = quote!(::mcnbt::convert::IntoTag::into_tag(#expr));
+                quote!({
+                    let mut tag = #tag;
+                    *tag.tag_name_mut() = #name;
+                    tag
+                })
+            }
+        }
+    }
+}
+
+impl ToTokens for Element {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Element::Literal(literal) => literal.to_tokens(tokens),
+            Element::Interpolated(expr) => expr.to_tokens(tokens),
+        }
+    }
+}
+
+/// Parses `#ident` or `#(expr)`.
+fn parse_interpolation(input: ParseStream) -> syn::Result<TokenStream> {
+    input.parse::<Token![#]>()?;
+    if input.peek(syn::token::Paren) {
+        let content;
+        parenthesized!(content in input);
+        let expr: TokenStream = content.parse()?;
+        Ok(quote!((#expr)))
+    } else {
+        Ok(input.call(Ident::parse_any)?.into_token_stream())
+    }
+}
+
+/// Parses an optional sign followed by a numeric literal and returns the
+/// literal together with its digits including the sign.
+fn parse_number(input: ParseStream) -> syn::Result<Option<(Lit, String)>> {
+    let mut sign = "";
+    let signed = input.peek(Token![-]) || input.peek(Token![+]);
+    if input.peek(Token![-]) {
+        input.parse::<Token![-]>()?;
+        sign = "-";
+    } else if input.peek(Token![+]) {
+        input.parse::<Token![+]>()?;
+    }
+
+    let is_number = input.peek(syn::LitInt) || input.peek(syn::LitFloat);
+    if signed && !is_number {
+        return Err(input.error("expected a number after the sign"));
+    }
+
+    let lit: Lit = match input.fork().parse() {
+        Ok(lit @ (Lit::Int(_) | Lit::Float(_))) => {
+            input.parse::<Lit>()?;
+            lit
+        }
+        _ => return Ok(None),
+    };
+    let digits = match &lit {
+        Lit::Int(lit) => lit.base10_digits(),
+        Lit::Float(lit) => lit.base10_digits(),
+        _ => unreachable!(),
+    };
+    let digits = format!("{}{}", sign, digits);
+    Ok(Some((lit, digits)))
+}
+
+fn out_of_range(lit: &Lit, type_name: &str) -> Error {
+    Error::new(lit.span(), format!("number out of range for {}", type_name))
+}
+
+/// Parses a number into a tag according to its suffix.
+fn number(lit: &Lit, digits: &str) -> syn::Result<Value> {
+    let (suffix, is_float) = match lit {
+        Lit::Int(lit) => (lit.suffix(), false),
+        Lit::Float(lit) => (lit.suffix(), true),
+        _ => unreachable!(),
+    };
+
+    let integer = |type_name| {
+        if is_float {
+            Err(Error::new(
+                lit.span(),
+                format!("expected an integer for {}", type_name),
+            ))
+        } else {
+            Ok(())
+        }
+    };
+
+    Ok(match suffix {
+        "b" | "B" => {
+            integer("TAG_Byte")?;
+            Value::Byte(digits.parse().map_err(|_| out_of_range(lit, "TAG_Byte"))?)
+        }
+        "s" | "S" => {
+            integer("TAG_Short")?;
+            Value::Short(digits.parse().map_err(|_| out_of_range(lit, "TAG_Short"))?)
+        }
+        "" if !is_float => Value::Int(digits.parse().map_err(|_| out_of_range(lit, "TAG_Int"))?),
+        "l" | "L" => {
+            integer("TAG_Long")?;
+            Value::Long(digits.parse().map_err(|_| out_of_range(lit, "TAG_Long"))?)
+        }
+        "f" | "F" => {
+            let value: f32 = digits.parse().map_err(|_| out_of_range(lit, "TAG_Float"))?;
+            if !value.is_finite() {
+                return Err(out_of_range(lit, "TAG_Float"));
+            }
+            Value::Float(value)
+        }
+        "" | "d" | "D" => {
+            let value: f64 = digits
+                .parse()
+                .map_err(|_| out_of_range(lit, "TAG_Double"))?;
+            if !value.is_finite() {
+                return Err(out_of_range(lit, "TAG_Double"));
+            }
+            Value::Double(value)
+        }
+        suffix => {
+            return Err(Error::new(
+                lit.span(),
+                format!("unknown suffix `{}`", suffix),
+            ))
+        }
+    })
+}
+
+/// Parses the elements of an array after the `B;`, `I;` or `L;` prefix.
+fn parse_array(input: ParseStream, array: Array) -> syn::Result<Value> {
+    let mut elements = vec![];
+    while !input.is_empty() {
+        if input.peek(Token![#]) {
+            elements.push(Element::Interpolated(parse_interpolation(input)?));
+        } else {
+            let span = input.span();
+            let Some((lit, digits)) = parse_number(input)? else {
+                return Err(Error::new(span, "expected a number"));
+            };
+            let literal = match (array, number(&lit, &digits)?) {
+                (Array::Byte, Value::Byte(value)) => Literal::i8_suffixed(value),
+                (Array::Byte, Value::Int(_)) => Literal::i8_suffixed(
+                    digits.parse().map_err(|_| out_of_range(&lit, "TAG_Byte"))?,
+                ),
+                (Array::Int, Value::Int(value)) => Literal::i32_suffixed(value),
+                (Array::Long, Value::Long(value)) => Literal::i64_suffixed(value),
+                (Array::Long, Value::Int(_)) => Literal::i64_suffixed(
+                    digits.parse().map_err(|_| out_of_range(&lit, "TAG_Long"))?,
+                ),
+                (_, value) => {
+                    return Err(Error::new(
+                        lit.span(),
+                        format!(
+                            "unexpected {} in array",
+                            value.type_name().unwrap_or_default()
+                        ),
+                    ))
+                }
+            };
+            elements.push(Element::Literal(literal));
+        }
+
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+
+    Ok(match array {
+        Array::Byte => Value::ByteArray(elements),
+        Array::Int => Value::IntArray(elements),
+        Array::Long => Value::LongArray(elements),
+    })
+}
+
+/// Parses the contents of square brackets which is either an array or a
+/// list.
+fn parse_list(input: ParseStream) -> syn::Result<Value> {
+    if input.peek(Ident) && input.peek2(Token![;]) {
+        let prefix: Ident = input.parse()?;
+        let array = match prefix.to_string().as_str() {
+            "B" => Array::Byte,
+            "I" => Array::Int,
+            "L" => Array::Long,
+            _ => {
+                return Err(Error::new(
+                    prefix.span(),
+                    "expected `B`, `I` or `L` as the type of the array",
+                ))
+            }
+        };
+        input.parse::<Token![;]>()?;
+        return parse_array(input, array);
+    }
+
+    let mut values: Vec<Value> = vec![];
+    let mut type_name = None;
+    while !input.is_empty() {
+        let span = input.span();
+        let value: Value = input.parse()?;
+
+        // interpolated values are not known until runtime
+        if let Some(name) = value.type_name() {
+            match type_name {
+                Some(expected) if expected != name => {
+                    return Err(Error::new(
+                        span,
+                        format!(
+                            "all elements of a list must be of the same type but found {} after {}",
+                            name, expected
+                        ),
+                    ))
+                }
+                _ => type_name = Some(name),
+            }
+        }
+        values.push(value);
+
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    Ok(Value::List(values))
+}
+
+/// Parses the contents of curly braces.
+fn parse_compound(input: ParseStream) -> syn::Result<Value> {
+    let mut entries: Vec<(Key, Value)> = vec![];
+    while !input.is_empty() {
+        let key = if input.peek(Token![#]) {
+            Key::Interpolated(parse_interpolation(input)?)
+        } else if input.peek(LitStr) {
+            let key: LitStr = input.parse()?;
+            Key::Literal(key.value(), key.span())
+        } else if input.peek(Ident::peek_any) {
+            let key = input.call(Ident::parse_any)?;
+            Key::Literal(key.to_string(), key.span())
+        } else {
+            return Err(input.error("expected a key"));
+        };
+
+        if let Key::Literal(name, span) = &key {
+            let duplicate = entries.iter().any(|(other, _)| match other {
+                Key::Literal(other, _) => other == name,
+                Key::Interpolated(_) => false,
+            });
+            if duplicate {
+                return Err(Error::new(*span, format!("duplicate key \"{}\"", name)));
+            }
+        }
+
+        input.parse::<Token![:]>()?;
+        entries.push((key, input.parse()?));
+
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    Ok(Value::Compound(entries))
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            parse_compound(&content)
+        } else if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            parse_list(&content)
+        } else if input.peek(Token![#]) {
+            parse_interpolation(input).map(Value::Interpolated)
+        } else if let Some((lit, digits)) = parse_number(input)? {
+            number(&lit, &digits)
+        } else if input.peek(LitStr) {
+            Ok(Value::String(input.parse::<LitStr>()?.value()))
+        } else if input.peek(syn::LitBool) {
+            Ok(Value::Byte(input.parse::<syn::LitBool>()?.value.into()))
+        } else if input.peek(Ident::peek_any) {
+            // unquoted string
+            Ok(Value::String(input.call(Ident::parse_any)?.to_string()))
+        } else {
+            Err(input.error("expected a value"))
+        }
+    }
+}
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let value: Value = syn::parse2(input)?;
+    Ok(value.expand(quote!(::std::option::Option::None)))
+}
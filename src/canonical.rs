@@ -0,0 +1,272 @@
+//! Canonical form of tags for comparisons and hashing.
+
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+use crate::tag::Tag;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Brings the tag and its descendants into their canonical form.
+///
+/// See [`Tag::canonicalize`] for details.
+pub fn canonicalize(tag: &mut Tag) {
+    match tag {
+        Tag::Float(_, payload) => {
+            if payload.is_nan() {
+                *payload = f32::NAN;
+            } else if *payload == 0.0 {
+                *payload = 0.0;
+            }
+        }
+        Tag::Double(_, payload) => {
+            if payload.is_nan() {
+                *payload = f64::NAN;
+            } else if *payload == 0.0 {
+                *payload = 0.0;
+            }
+        }
+        Tag::List(_, elements) => elements.iter_mut().for_each(canonicalize),
+        Tag::Compound(_, children) => {
+            children.iter_mut().for_each(canonicalize);
+            children.sort_by(|a, b| a.tag_name().cmp(&b.tag_name()));
+        }
+        _ => {}
+    }
+}
+
+/// Passes an unambiguous encoding of a canonical tag to `write`.
+///
+/// Unlike the binary format, lengths and floats are encoded in a fixed way
+/// independent of the byte order and the name of the tag itself is omitted.
+fn encode(tag: &Tag, write: &mut impl FnMut(&[u8])) {
+    fn len(len: usize, write: &mut impl FnMut(&[u8])) {
+        write(&(len as u64).to_be_bytes());
+    }
+
+    let id: u8 = match tag {
+        Tag::Byte(..) => 1,
+        Tag::Short(..) => 2,
+        Tag::Int(..) => 3,
+        Tag::Long(..) => 4,
+        Tag::Float(..) => 5,
+        Tag::Double(..) => 6,
+        Tag::ByteArray(..) => 7,
+        Tag::String(..) => 8,
+        Tag::List(..) => 9,
+        Tag::Compound(..) => 10,
+        Tag::IntArray(..) => 11,
+        Tag::LongArray(..) => 12,
+    };
+    write(&[id]);
+
+    match tag {
+        Tag::Byte(_, payload) => write(&payload.to_be_bytes()),
+        Tag::Short(_, payload) => write(&payload.to_be_bytes()),
+        Tag::Int(_, payload) => write(&payload.to_be_bytes()),
+        Tag::Long(_, payload) => write(&payload.to_be_bytes()),
+        Tag::Float(_, payload) => write(&payload.to_bits().to_be_bytes()),
+        Tag::Double(_, payload) => write(&payload.to_bits().to_be_bytes()),
+        Tag::ByteArray(_, payload) => {
+            len(payload.len(), write);
+            payload.iter().for_each(|byte| write(&byte.to_be_bytes()));
+        }
+        Tag::String(_, payload) => {
+            len(payload.len(), write);
+            write(payload.as_bytes());
+        }
+        Tag::List(_, elements) => {
+            len(elements.len(), write);
+            elements.iter().for_each(|element| encode(element, write));
+        }
+        Tag::Compound(_, children) => {
+            len(children.len(), write);
+            for child in children {
+                let name = child.tag_name().unwrap_or_default();
+                len(name.len(), write);
+                write(name.as_bytes());
+                encode(child, write);
+            }
+        }
+        Tag::IntArray(_, payload) => {
+            len(payload.len(), write);
+            payload.iter().for_each(|int| write(&int.to_be_bytes()));
+        }
+        Tag::LongArray(_, payload) => {
+            len(payload.len(), write);
+            payload.iter().for_each(|long| write(&long.to_be_bytes()));
+        }
+    }
+}
+
+/// Returns `true` if two canonical tags are identical apart from the names
+/// of the tags themselves.
+fn identical(a: &Tag, b: &Tag) -> bool {
+    match (a, b) {
+        (Tag::Float(_, x), Tag::Float(_, y)) => x.to_bits() == y.to_bits(),
+        (Tag::Double(_, x), Tag::Double(_, y)) => x.to_bits() == y.to_bits(),
+        (Tag::List(_, x), Tag::List(_, y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| identical(x, y))
+        }
+        (Tag::Compound(_, x), Tag::Compound(_, y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y)
+                    .all(|(x, y)| x.tag_name() == y.tag_name() && identical(x, y))
+        }
+        (Tag::Byte(_, x), Tag::Byte(_, y)) => x == y,
+        (Tag::Short(_, x), Tag::Short(_, y)) => x == y,
+        (Tag::Int(_, x), Tag::Int(_, y)) => x == y,
+        (Tag::Long(_, x), Tag::Long(_, y)) => x == y,
+        (Tag::ByteArray(_, x), Tag::ByteArray(_, y)) => x == y,
+        (Tag::String(_, x), Tag::String(_, y)) => x == y,
+        (Tag::IntArray(_, x), Tag::IntArray(_, y)) => x == y,
+        (Tag::LongArray(_, x), Tag::LongArray(_, y)) => x == y,
+        _ => false,
+    }
+}
+
+/// A tag in its canonical form which implements [`Eq`] and [`Hash`].
+///
+/// Two canonical tags are equal if their payloads are equal regardless of
+/// the order of the children of compounds. Floats are compared by their bits
+/// after all NaNs were replaced with the same value and `-0.0` with `0.0`.
+/// The names of the tags themselves are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashSet;
+/// use mcnbt::{Tag, byte, compound, string};
+/// use mcnbt::canonical::Canonical;
+///
+/// let a = compound!(string!("id" => "minecraft:stone"), byte!("Count" => 1));
+/// let b = compound!(byte!("Count" => 1), string!("id" => "minecraft:stone"));
+///
+/// let stacks: HashSet<Canonical> = [a, b].into_iter().map(Canonical::new).collect();
+/// assert_eq!(stacks.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Canonical(Tag);
+
+impl Canonical {
+    /// Brings the tag into its canonical form.
+    pub fn new(mut tag: Tag) -> Self {
+        canonicalize(&mut tag);
+        Self(tag)
+    }
+
+    /// Returns the canonical tag.
+    pub fn into_inner(self) -> Tag {
+        self.0
+    }
+
+    /// Returns a hash of the content of the tag.
+    ///
+    /// Unlike [`Hash`], the hash is computed with 64-bit FNV-1a over a fixed
+    /// encoding and is therefore the same on every platform and in every run
+    /// of the program.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        encode(&self.0, &mut |bytes| {
+            for byte in bytes {
+                hash ^= u64::from(*byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        });
+        hash
+    }
+}
+
+impl From<Tag> for Canonical {
+    fn from(tag: Tag) -> Self {
+        Self::new(tag)
+    }
+}
+
+impl From<Canonical> for Tag {
+    fn from(canonical: Canonical) -> Self {
+        canonical.0
+    }
+}
+
+impl Deref for Canonical {
+    type Target = Tag;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl PartialEq for Canonical {
+    fn eq(&self, other: &Self) -> bool {
+        identical(&self.0, &other.0)
+    }
+}
+
+impl Eq for Canonical {}
+
+impl Hash for Canonical {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        encode(&self.0, &mut |bytes| state.write(bytes));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, double, float, int, list, string};
+
+    #[test]
+    fn normalization() {
+        let a = compound!(
+            float!("nan" => f32::from_bits(0x7fc0_0001)),
+            double!("zero" => -0.0),
+            list!("list" => compound!(byte!("b" => 1), byte!("a" => 2))),
+        );
+        let b = compound!("name" =>
+            list!("list" => compound!(byte!("a" => 2), byte!("b" => 1))),
+            double!("zero" => 0.0),
+            float!("nan" => f32::NAN),
+        );
+        let (a, b) = (Canonical::new(a), Canonical::new(b));
+        assert_eq!(a, b);
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let names: Vec<_> = a.as_compound().unwrap().iter().map(Tag::tag_name).collect();
+        assert_eq!(names, [Some("list"), Some("nan"), Some("zero")]);
+        assert_eq!(a["zero"].as_double().unwrap().to_bits(), 0.0f64.to_bits());
+        assert_eq!(a["nan"].as_float().unwrap().to_bits(), f32::NAN.to_bits());
+    }
+
+    #[test]
+    fn differences() {
+        let tags = [
+            int!(1),
+            Tag::Long(None, 1),
+            list!(int!(1)),
+            list!(list!(int!(1))),
+            compound!(int!("a" => 1)),
+            compound!(int!("b" => 1)),
+            string!(""),
+            Tag::IntArray(None, vec![]),
+            Tag::LongArray(None, vec![]),
+        ];
+        for (i, a) in tags.iter().enumerate() {
+            for (j, b) in tags.iter().enumerate() {
+                let (a, b) = (Canonical::new(a.clone()), Canonical::new(b.clone()));
+                assert_eq!(a == b, i == j);
+                assert_eq!(a.content_hash() == b.content_hash(), i == j);
+            }
+        }
+    }
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(
+            Canonical::new(byte!(1)).content_hash(),
+            0x082f_2307_b4e8_8e77
+        );
+    }
+}
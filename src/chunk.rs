@@ -0,0 +1,93 @@
+//! Decoding of the block, biome, heightmap and light data of chunks.
+//!
+//! Chunks store most of their data in compact arrays. Block states of a
+//! section of 16×16×16 blocks are stored as a palette of block state
+//! compounds and a [`Tag::LongArray`](crate::Tag::LongArray) of indices into
+//! that palette, each using as few bits as possible.
+//!
+//! Older chunks use other layouts which [`SectionBlocks`] reads depending on
+//! the data version of the chunk. Likewise, [`Biomes`] reads and writes the
+//! biomes of every chunk generation, [`Heightmap`] the packed heightmaps and
+//! [`Light`] the nibble arrays storing sky and block light.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mcnbt::chunk::BlockStates;
+//! use mcnbt::{Tag, byte, compound, list, string};
+//!
+//! let stone = compound!(string!("Name" => "minecraft:stone"));
+//! let mut blocks = BlockStates::filled(stone.clone());
+//! blocks.set(1, 2, 3, compound!(string!("Name" => "minecraft:dirt")));
+//!
+//! let section = compound!(byte!("Y" => 0), blocks.to_tag());
+//! let blocks = BlockStates::from_section(&section).unwrap();
+//! assert_eq!(blocks.get(0, 0, 0), &stone);
+//! assert_eq!(blocks.get(1, 2, 3)["Name"].as_str(), Some("minecraft:dirt"));
+//! ```
+
+mod biomes;
+mod block_states;
+mod heightmap;
+mod legacy;
+mod light;
+pub mod packed;
+mod section;
+
+pub use biomes::{Biomes, SectionBiomes, BIOME_VOLUME, BIOME_WIDTH};
+pub use block_states::BlockStates;
+pub use heightmap::{Heightmap, HEIGHTMAP_BITS};
+pub use legacy::LegacyBlocks;
+pub use light::{Light, LightLayer};
+pub use section::{SectionBlocks, BLOCK_STATES_DATA_VERSION, FLATTENING_DATA_VERSION};
+
+use crate::tag::Tag;
+
+/// The amount of blocks along each axis of a section.
+pub const SECTION_WIDTH: usize = 16;
+
+/// The amount of blocks in a section.
+pub const SECTION_VOLUME: usize = SECTION_WIDTH * SECTION_WIDTH * SECTION_WIDTH;
+
+/// Returns the data version of a chunk or zero if it has none, which is the
+/// case for chunks saved before Minecraft 1.9.
+pub fn data_version(chunk: &Tag) -> i32 {
+    chunk
+        .get("DataVersion")
+        .and_then(Tag::as_int)
+        .unwrap_or_default()
+}
+
+/// Returns the sections of a chunk, which are stored in `sections` since
+/// Minecraft 1.18 and in `Level.Sections` before.
+pub fn sections(chunk: &Tag) -> &[Tag] {
+    chunk
+        .get("sections")
+        .or_else(|| chunk.get("Level")?.get("Sections"))
+        .and_then(Tag::as_list)
+        .unwrap_or_default()
+}
+
+/// Replaces the child of a compound with the same name as the given tag or
+/// appends the tag if there is none.
+fn set_child(compound: &mut Vec<Tag>, tag: Tag) {
+    match compound
+        .iter_mut()
+        .find(|child| child.tag_name() == tag.tag_name())
+    {
+        Some(child) => *child = tag,
+        None => compound.push(tag),
+    }
+}
+
+/// Returns the index of a block within a section ordered by y, z and x.
+fn index(x: usize, y: usize, z: usize) -> usize {
+    assert!(
+        x < SECTION_WIDTH && y < SECTION_WIDTH && z < SECTION_WIDTH,
+        "the coordinates {}, {}, {} are outside of the section",
+        x,
+        y,
+        z
+    );
+    (y * SECTION_WIDTH + z) * SECTION_WIDTH + x
+}
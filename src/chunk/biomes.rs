@@ -0,0 +1,441 @@
+use super::packed::{pack, palette_bits, unpack};
+use super::{data_version, set_child, FLATTENING_DATA_VERSION};
+use crate::convert::FromTag;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The amount of biome cells along each axis of a section.
+pub const BIOME_WIDTH: usize = 4;
+
+/// The amount of biome cells in a section.
+pub const BIOME_VOLUME: usize = BIOME_WIDTH * BIOME_WIDTH * BIOME_WIDTH;
+
+/// The amount of columns in a chunk, each having one biome before Minecraft
+/// 1.15.
+const COLUMNS: usize = 256;
+
+/// The biomes of a section of a chunk saved by Minecraft 1.18 or later.
+///
+/// Every cell of 4×4×4 blocks is an index into the palette of biome names
+/// such as `minecraft:plains`. Cells are ordered by their y, z and then x
+/// coordinate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionBiomes {
+    palette: Vec<String>,
+    indices: Vec<u8>,
+}
+
+impl SectionBiomes {
+    /// Creates biomes from a palette and the indices of all 64 cells into it.
+    pub fn new(palette: Vec<String>, indices: Vec<u8>) -> Result<Self, Error> {
+        if palette.is_empty() {
+            return Err(Error::InvalidChunkData("the palette is empty".to_string()));
+        }
+        if indices.len() != BIOME_VOLUME {
+            return Err(Error::InvalidChunkData(format!(
+                "expected {} biomes but found {}",
+                BIOME_VOLUME,
+                indices.len()
+            )));
+        }
+        if let Some(index) = indices.iter().find(|i| usize::from(**i) >= palette.len()) {
+            return Err(Error::InvalidChunkData(format!(
+                "the palette index {} is out of bounds",
+                index
+            )));
+        }
+        Ok(Self { palette, indices })
+    }
+
+    /// Creates biomes where every cell has the given biome.
+    pub fn filled(biome: impl Into<String>) -> Self {
+        Self {
+            palette: vec![biome.into()],
+            indices: vec![0; BIOME_VOLUME],
+        }
+    }
+
+    /// Reads the `biomes` of a section of a chunk.
+    pub fn from_section(section: &Tag) -> Result<Self, Error> {
+        section
+            .get("biomes")
+            .ok_or_else(|| Error::MissingTag("biomes".to_string()))
+            .and_then(Self::from_tag)
+    }
+
+    /// Returns the distinct biomes.
+    pub fn palette(&self) -> &[String] {
+        &self.palette
+    }
+
+    /// Returns the indices into the palette of all cells.
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+
+    /// Returns the biome of the cell at the given cell coordinates within the
+    /// section, which are the block coordinates divided by four.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 4.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &str {
+        &self.palette[usize::from(self.indices[index(x, y, z)])]
+    }
+
+    /// Changes the biome of the cell at the given cell coordinates within the
+    /// section and adds it to the palette if necessary.
+    ///
+    /// Unused biomes are removed from the palette once it has as many
+    /// entries as there are cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 4.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, biome: &str) {
+        let cell = index(x, y, z);
+        let i = match self.palette.iter().position(|entry| entry == biome) {
+            Some(i) => i,
+            None => {
+                if self.palette.len() >= BIOME_VOLUME {
+                    self.remove_unused(cell);
+                }
+                self.palette.push(biome.to_string());
+                self.palette.len() - 1
+            }
+        };
+        self.indices[cell] = i as u8;
+    }
+
+    /// Removes the biomes which are not used by any cell except the given
+    /// one, whose index becomes invalid until it is set.
+    fn remove_unused(&mut self, except: usize) {
+        let mut used = vec![false; self.palette.len()];
+        for (cell, i) in self.indices.iter().enumerate() {
+            used[usize::from(*i)] |= cell != except;
+        }
+        let mut mapping = vec![0; self.palette.len()];
+        let mut next = 0;
+        for (i, used) in used.iter().enumerate() {
+            if *used {
+                self.palette.swap(next, i);
+                mapping[i] = next as u8;
+                next += 1;
+            }
+        }
+        self.palette.truncate(next);
+        for i in &mut self.indices {
+            *i = mapping[usize::from(*i)];
+        }
+    }
+
+    /// Returns the `biomes` compound of a section.
+    ///
+    /// The indices are packed with as few bits as possible and `data` is
+    /// omitted if the palette has a single entry.
+    pub fn to_tag(&self) -> Tag {
+        let palette = self
+            .palette
+            .iter()
+            .map(|biome| Tag::String(None, biome.clone()))
+            .collect();
+        let mut children = vec![Tag::List(Some("palette".to_string()), palette)];
+        let bits = palette_bits(self.palette.len(), 0);
+        if bits != 0 {
+            let indices: Vec<u32> = self.indices.iter().map(|i| u32::from(*i)).collect();
+            children.push(Tag::LongArray(
+                Some("data".to_string()),
+                pack(&indices, bits),
+            ));
+        }
+        Tag::Compound(Some("biomes".to_string()), children)
+    }
+}
+
+/// Reads the `biomes` compound of a section.
+impl FromTag for SectionBiomes {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        let palette: Vec<String> = tag
+            .get("palette")
+            .ok_or_else(|| Error::MissingTag("palette".to_string()))
+            .and_then(FromTag::from_tag)?;
+        let bits = palette_bits(palette.len(), 0);
+        let data = match tag.get("data") {
+            Some(data) => data.as_long_array().ok_or(Error::UnexpectedType {
+                expected: "TAG_Long_Array",
+                found: data.name(),
+            })?,
+            None => &[],
+        };
+        let indices = unpack(data, bits, BIOME_VOLUME)?
+            .into_iter()
+            .map(|i| i as u8)
+            .collect();
+        Self::new(palette, indices)
+    }
+}
+
+/// The biomes of a chunk in any layout used by the game.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::chunk::{Biomes, SectionBiomes};
+/// use mcnbt::{Tag, byte, compound, int, list};
+///
+/// let mut chunk = compound!("" => int!("DataVersion" => 3465), list!("sections" => compound!(byte!("Y" => 0))));
+/// let mut biomes = SectionBiomes::filled("minecraft:plains");
+/// biomes.set(0, 0, 0, "minecraft:river");
+/// Biomes::Sections(vec![(0, biomes.clone())]).write_to_chunk(&mut chunk).unwrap();
+///
+/// assert_eq!(Biomes::from_chunk(&chunk).unwrap(), Some(Biomes::Sections(vec![(0, biomes)])));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Biomes {
+    /// The numeric biome of each column of blocks ordered by z and then x,
+    /// used before Minecraft 1.15.
+    Columns(Vec<i32>),
+    /// The numeric biome of each cell of 4×4×4 blocks ordered by y, z and
+    /// then x, used by Minecraft 1.15 up to 1.17.
+    Cells(Vec<i32>),
+    /// The biomes of each section together with its y coordinate, used since
+    /// Minecraft 1.18.
+    Sections(Vec<(i32, SectionBiomes)>),
+}
+
+impl Biomes {
+    /// Reads the biomes of a chunk or returns [`None`] if the chunk has none,
+    /// which happens for chunks whose generation has not reached the biomes
+    /// yet.
+    ///
+    /// Before Minecraft 1.18, the biomes are stored in `Level.Biomes` as a
+    /// `TAG_Byte_Array` or, since Minecraft 1.13, as a `TAG_Int_Array`.
+    /// Columns and cells are told apart by the length of the array. The
+    /// biomes of sections are stored in `Level.Sections` by the first
+    /// snapshots of Minecraft 1.18 and in `sections` since.
+    pub fn from_chunk(chunk: &Tag) -> Result<Option<Self>, Error> {
+        if let Some(biomes) = chunk.get("Level").and_then(|level| level.get("Biomes")) {
+            return match biomes {
+                Tag::ByteArray(_, bytes) if bytes.len() == COLUMNS => Ok(Some(Biomes::Columns(
+                    bytes.iter().map(|b| i32::from(*b as u8)).collect(),
+                ))),
+                Tag::IntArray(_, ints) if ints.len() == COLUMNS => {
+                    Ok(Some(Biomes::Columns(ints.clone())))
+                }
+                Tag::IntArray(_, ints) if !ints.is_empty() && ints.len() % 16 == 0 => {
+                    Ok(Some(Biomes::Cells(ints.clone())))
+                }
+                Tag::ByteArray(_, _) | Tag::IntArray(_, _) => Err(Error::InvalidChunkData(
+                    "unexpected length of the biomes".to_string(),
+                )),
+                tag => Err(Error::UnexpectedType {
+                    expected: "TAG_Int_Array",
+                    found: tag.name(),
+                }),
+            };
+        }
+
+        let mut sections = vec![];
+        for section in super::sections(chunk) {
+            let Some(biomes) = section.get("biomes") else {
+                continue;
+            };
+            let y = section
+                .get("Y")
+                .and_then(Tag::as_i64_lossy)
+                .ok_or_else(|| Error::MissingTag("Y".to_string()))?;
+            sections.push((y as i32, SectionBiomes::from_tag(biomes)?));
+        }
+        if sections.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Biomes::Sections(sections)))
+    }
+
+    /// Replaces the biomes of a chunk.
+    ///
+    /// Columns and cells are written to `Level.Biomes`, which must exist.
+    /// Columns are stored as a `TAG_Byte_Array` if the data version of the
+    /// chunk is before Minecraft 1.13. Sections are written to
+    /// `Level.Sections` if the chunk has a `Level` and to `sections`
+    /// otherwise, adding missing sections.
+    pub fn write_to_chunk(&self, chunk: &mut Tag) -> Result<(), Error> {
+        let data_version = data_version(chunk);
+        let name = Some("Biomes".to_string());
+        let biomes = match self {
+            Biomes::Columns(ids) if data_version < FLATTENING_DATA_VERSION => {
+                Tag::ByteArray(name, ids.iter().map(|id| *id as i8).collect())
+            }
+            Biomes::Columns(ids) | Biomes::Cells(ids) => Tag::IntArray(name, ids.clone()),
+            Biomes::Sections(biomes) => return write_sections(chunk, biomes),
+        };
+        let level = chunk
+            .get_mut("Level")
+            .and_then(Tag::as_compound_mut)
+            .ok_or_else(|| Error::MissingTag("Level".to_string()))?;
+        set_child(level, biomes);
+        Ok(())
+    }
+}
+
+/// Writes the `biomes` of the sections of a chunk saved by Minecraft 1.18 or
+/// later.
+fn write_sections(chunk: &mut Tag, biomes: &[(i32, SectionBiomes)]) -> Result<(), Error> {
+    let (parent, name) = if chunk.get("Level").is_some() {
+        (&mut chunk["Level"], "Sections")
+    } else {
+        (chunk, "sections")
+    };
+    let found = parent.name();
+    let compound = parent.as_compound_mut().ok_or(Error::UnexpectedType {
+        expected: "TAG_Compound",
+        found,
+    })?;
+    if !compound.iter().any(|tag| tag.tag_name() == Some(name)) {
+        compound.push(Tag::List(Some(name.to_string()), vec![]));
+    }
+    let sections = &mut parent[name];
+    let found = sections.name();
+    let sections = sections.as_list_mut().ok_or(Error::UnexpectedType {
+        expected: "TAG_List",
+        found,
+    })?;
+    for (y, biomes) in biomes {
+        let position = sections.iter().position(|section| {
+            section.get("Y").and_then(Tag::as_i64_lossy) == Some(i64::from(*y))
+        });
+        let section = match position {
+            Some(i) => &mut sections[i],
+            None => {
+                let y = Tag::Byte(Some("Y".to_string()), *y as i8);
+                sections.push(Tag::Compound(None, vec![y]));
+                sections.last_mut().unwrap()
+            }
+        };
+        let found = section.name();
+        let section = section.as_compound_mut().ok_or(Error::UnexpectedType {
+            expected: "TAG_Compound",
+            found,
+        })?;
+        set_child(section, biomes.to_tag());
+    }
+    Ok(())
+}
+
+/// Returns the index of a cell within a section ordered by y, z and x.
+fn index(x: usize, y: usize, z: usize) -> usize {
+    assert!(
+        x < BIOME_WIDTH && y < BIOME_WIDTH && z < BIOME_WIDTH,
+        "the cell coordinates {}, {}, {} are outside of the section",
+        x,
+        y,
+        z
+    );
+    (y * BIOME_WIDTH + z) * BIOME_WIDTH + x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list, string};
+
+    #[test]
+    fn section() {
+        let tag = compound!(
+            list!("palette" => string!("minecraft:plains"), string!("minecraft:river")),
+            Tag::LongArray(Some("data".to_string()), vec![0b10]),
+        );
+        let biomes = SectionBiomes::from_tag(&tag).unwrap();
+        assert_eq!(biomes.get(0, 0, 0), "minecraft:plains");
+        assert_eq!(biomes.get(1, 0, 0), "minecraft:river");
+        assert_eq!(biomes.to_tag()["data"], tag["data"]);
+
+        let mut biomes = SectionBiomes::filled("minecraft:plains");
+        assert_eq!(biomes.to_tag().get("data"), None);
+        for i in 0..5 {
+            biomes.set(i % 4, 3, i / 4, &format!("minecraft:biome_{}", i));
+        }
+        let tag = biomes.to_tag();
+        // six entries need three bits, so 21 fit into a long
+        assert_eq!(tag["data"].as_long_array().unwrap().len(), 4);
+        assert_eq!(SectionBiomes::from_tag(&tag).unwrap(), biomes);
+
+        let invalid = compound!(
+            list!("palette" => string!("minecraft:plains"), string!("minecraft:river")),
+            Tag::LongArray(Some("data".to_string()), vec![0; 2]),
+        );
+        assert!(matches!(
+            SectionBiomes::from_tag(&invalid),
+            Err(Error::InvalidChunkData(_))
+        ));
+    }
+
+    #[test]
+    fn legacy() {
+        let mut chunk = compound!("" => compound!("Level" =>
+            Tag::ByteArray(Some("Biomes".to_string()), vec![-127; 256]),
+        ));
+        let biomes = Biomes::from_chunk(&chunk).unwrap().unwrap();
+        assert_eq!(biomes, Biomes::Columns(vec![129; 256]));
+        biomes.write_to_chunk(&mut chunk).unwrap();
+        assert_eq!(
+            chunk["Level"]["Biomes"].as_byte_array(),
+            Some(&[-127; 256][..])
+        );
+
+        let mut chunk = compound!("" =>
+            int!("DataVersion" => 2586),
+            compound!("Level" => Tag::IntArray(Some("Biomes".to_string()), vec![1; 1024])),
+        );
+        let Some(Biomes::Cells(mut ids)) = Biomes::from_chunk(&chunk).unwrap() else {
+            panic!("expected cells");
+        };
+        ids[0] = 7;
+        Biomes::Cells(ids).write_to_chunk(&mut chunk).unwrap();
+        assert_eq!(
+            chunk["Level"]["Biomes"].as_int_array().unwrap()[..2],
+            [7, 1]
+        );
+        assert_eq!(chunk["Level"].as_compound().unwrap().len(), 1);
+
+        let generating = compound!("" => compound!("Level" => list!("Sections" =>)));
+        assert_eq!(Biomes::from_chunk(&generating).unwrap(), None);
+        let invalid = compound!("" => compound!("Level" =>
+            Tag::IntArray(Some("Biomes".to_string()), vec![1; 100]),
+        ));
+        assert!(Biomes::from_chunk(&invalid).is_err());
+    }
+
+    #[test]
+    fn sections() {
+        let mut chunk = compound!("" =>
+            int!("DataVersion" => 3465),
+            list!("sections" => compound!(
+                byte!("Y" => -4),
+                SectionBiomes::filled("minecraft:plains").to_tag(),
+            )),
+        );
+        let biomes = Biomes::Sections(vec![
+            (-4, SectionBiomes::filled("minecraft:desert")),
+            (2, SectionBiomes::filled("minecraft:river")),
+        ]);
+        biomes.write_to_chunk(&mut chunk).unwrap();
+        assert_eq!(chunk["sections"].as_list().unwrap().len(), 2);
+        assert_eq!(chunk["sections"][0].as_compound().unwrap().len(), 2);
+        assert_eq!(Biomes::from_chunk(&chunk).unwrap(), Some(biomes));
+
+        // the first snapshots of 1.18 still wrap the sections in `Level`
+        let mut chunk = compound!("" =>
+            int!("DataVersion" => 2840),
+            compound!("Level" => list!("Sections" => compound!(byte!("Y" => -4)))),
+        );
+        let biomes = Biomes::Sections(vec![(-4, SectionBiomes::filled("minecraft:desert"))]);
+        biomes.write_to_chunk(&mut chunk).unwrap();
+        assert_eq!(
+            chunk["Level"]["Sections"][0]["biomes"],
+            SectionBiomes::filled("minecraft:desert").to_tag()
+        );
+        assert_eq!(chunk.get("sections"), None);
+        assert_eq!(Biomes::from_chunk(&chunk).unwrap(), Some(biomes));
+    }
+}
@@ -0,0 +1,296 @@
+use super::packed::{pack, palette_bits, unpack, Packing};
+use super::{index, SECTION_VOLUME};
+use crate::convert::FromTag;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The minimum amount of bits per block in the packed data.
+const MIN_BITS: u32 = 4;
+
+/// The block states of a section of 16×16×16 blocks.
+///
+/// Every block is an index into the palette of block state compounds such as
+/// `{Name: "minecraft:furnace", Properties: {facing: "north", lit: "false"}}`.
+/// Blocks are ordered by their y, z and then x coordinate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockStates {
+    palette: Vec<Tag>,
+    indices: Vec<u16>,
+}
+
+impl BlockStates {
+    /// Creates block states from a palette and the indices of all 4096
+    /// blocks into it.
+    pub fn new(palette: Vec<Tag>, indices: Vec<u16>) -> Result<Self, Error> {
+        if palette.is_empty() {
+            return Err(Error::InvalidChunkData("the palette is empty".to_string()));
+        }
+        if indices.len() != SECTION_VOLUME {
+            return Err(Error::InvalidChunkData(format!(
+                "expected {} blocks but found {}",
+                SECTION_VOLUME,
+                indices.len()
+            )));
+        }
+        if let Some(index) = indices.iter().find(|i| usize::from(**i) >= palette.len()) {
+            return Err(Error::InvalidChunkData(format!(
+                "the palette index {} is out of bounds",
+                index
+            )));
+        }
+        let palette = palette.into_iter().map(unnamed).collect();
+        Ok(Self { palette, indices })
+    }
+
+    /// Creates block states where every block has the given state.
+    pub fn filled(state: Tag) -> Self {
+        Self {
+            palette: vec![unnamed(state)],
+            indices: vec![0; SECTION_VOLUME],
+        }
+    }
+
+    /// Reads the `block_states` of a section of a chunk saved by Minecraft
+    /// 1.18 or later.
+    pub fn from_section(section: &Tag) -> Result<Self, Error> {
+        section
+            .get("block_states")
+            .ok_or_else(|| Error::MissingTag("block_states".to_string()))
+            .and_then(Self::from_tag)
+    }
+
+    /// Creates block states from the `Palette` and `BlockStates` of a section
+    /// of a chunk saved by Minecraft 1.13 up to 1.17.
+    ///
+    /// Unlike later versions, the indices always use at least four bits.
+    pub fn from_packed(palette: Vec<Tag>, data: &[i64], packing: Packing) -> Result<Self, Error> {
+        let bits = palette_bits(palette.len(), MIN_BITS).max(MIN_BITS);
+        let indices = packing
+            .unpack(data, bits, SECTION_VOLUME)?
+            .into_iter()
+            .map(|i| i as u16)
+            .collect();
+        Self::new(palette, indices)
+    }
+
+    /// Packs the indices like Minecraft 1.13 up to 1.17 did, always using at
+    /// least four bits.
+    pub fn to_packed(&self, packing: Packing) -> Vec<i64> {
+        let bits = palette_bits(self.palette.len(), MIN_BITS).max(MIN_BITS);
+        packing.pack(&self.indices_u32(), bits)
+    }
+
+    /// Returns the distinct block states.
+    pub fn palette(&self) -> &[Tag] {
+        &self.palette
+    }
+
+    /// Returns the indices into the palette of all blocks.
+    pub fn indices(&self) -> &[u16] {
+        &self.indices
+    }
+
+    /// Returns the state of the block at the given coordinates within the
+    /// section.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &Tag {
+        &self.palette[usize::from(self.indices[index(x, y, z)])]
+    }
+
+    /// Changes the state of the block at the given coordinates within the
+    /// section and adds it to the palette if necessary.
+    ///
+    /// Unused states are removed from the palette once it has as many
+    /// entries as there are blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, state: Tag) {
+        let state = unnamed(state);
+        let block = index(x, y, z);
+        let i = match self.palette.iter().position(|entry| *entry == state) {
+            Some(i) => i,
+            None => {
+                if self.palette.len() >= SECTION_VOLUME {
+                    self.remove_unused(block);
+                }
+                self.palette.push(state);
+                self.palette.len() - 1
+            }
+        };
+        self.indices[block] = i as u16;
+    }
+
+    /// Removes the states which are not used by any block except the given
+    /// one, whose index becomes invalid until it is set.
+    fn remove_unused(&mut self, except: usize) {
+        let mut used = vec![false; self.palette.len()];
+        for (block, i) in self.indices.iter().enumerate() {
+            used[usize::from(*i)] |= block != except;
+        }
+        let mut mapping = vec![0; self.palette.len()];
+        let mut next = 0;
+        for (i, used) in used.iter().enumerate() {
+            if *used {
+                self.palette.swap(next, i);
+                mapping[i] = next as u16;
+                next += 1;
+            }
+        }
+        self.palette.truncate(next);
+        for i in &mut self.indices {
+            *i = mapping[usize::from(*i)];
+        }
+    }
+
+    /// Returns the `block_states` compound of a section.
+    ///
+    /// The indices are packed with as few bits as possible but at least four
+    /// and `data` is omitted if the palette has a single entry.
+    pub fn to_tag(&self) -> Tag {
+        let mut children = vec![Tag::List(Some("palette".to_string()), self.palette.clone())];
+        let bits = palette_bits(self.palette.len(), MIN_BITS);
+        if bits != 0 {
+            children.push(Tag::LongArray(
+                Some("data".to_string()),
+                pack(&self.indices_u32(), bits),
+            ));
+        }
+        Tag::Compound(Some("block_states".to_string()), children)
+    }
+
+    fn indices_u32(&self) -> Vec<u32> {
+        self.indices.iter().map(|i| u32::from(*i)).collect()
+    }
+}
+
+/// Reads the `block_states` compound of a section.
+impl FromTag for BlockStates {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        let palette: Vec<Tag> = tag
+            .get("palette")
+            .ok_or_else(|| Error::MissingTag("palette".to_string()))
+            .and_then(FromTag::from_tag)?;
+        let bits = palette_bits(palette.len(), MIN_BITS);
+        let data = match tag.get("data") {
+            Some(data) => data.as_long_array().ok_or(Error::UnexpectedType {
+                expected: "TAG_Long_Array",
+                found: data.name(),
+            })?,
+            None => &[],
+        };
+        let indices = unpack(data, bits, SECTION_VOLUME)?
+            .into_iter()
+            .map(|i| i as u16)
+            .collect();
+        Self::new(palette, indices)
+    }
+}
+
+fn unnamed(mut tag: Tag) -> Tag {
+    *tag.tag_name_mut() = None;
+    tag
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compound, list, string};
+
+    fn block(name: &str) -> Tag {
+        compound!(string!("Name" => name))
+    }
+
+    #[test]
+    fn decode() {
+        let mut data = vec![0; 256];
+        // the block at 1, 0, 0 uses the second entry and the block at 0, 1, 0
+        // the last one
+        data[0] = 1 << 4;
+        data[16] = 2;
+        let tag = compound!(
+            list!("palette" => block("minecraft:air"), block("minecraft:stone"), block("minecraft:dirt")),
+            Tag::LongArray(Some("data".to_string()), data),
+        );
+        let blocks = BlockStates::from_tag(&tag).unwrap();
+        assert_eq!(blocks.get(0, 0, 0), &block("minecraft:air"));
+        assert_eq!(blocks.get(1, 0, 0), &block("minecraft:stone"));
+        assert_eq!(blocks.get(0, 1, 0), &block("minecraft:dirt"));
+        assert_eq!(blocks.to_tag()["data"], tag["data"]);
+
+        let single = compound!(list!("palette" => block("minecraft:air")));
+        let blocks = BlockStates::from_tag(&single).unwrap();
+        assert_eq!(blocks.indices(), [0; SECTION_VOLUME]);
+        assert_eq!(blocks.to_tag().get("data"), None);
+    }
+
+    #[test]
+    fn invalid() {
+        let tag = compound!(
+            list!("palette" => block("minecraft:air"), block("minecraft:stone")),
+            Tag::LongArray(Some("data".to_string()), vec![0; 255]),
+        );
+        assert!(matches!(
+            BlockStates::from_tag(&tag),
+            Err(Error::InvalidChunkData(_))
+        ));
+        let tag = compound!(
+            list!("palette" => block("minecraft:air"), block("minecraft:stone")),
+            Tag::LongArray(Some("data".to_string()), vec![2; 256]),
+        );
+        assert!(matches!(
+            BlockStates::from_tag(&tag),
+            Err(Error::InvalidChunkData(_))
+        ));
+        assert!(BlockStates::from_tag(&compound!(list!("palette" =>))).is_err());
+    }
+
+    #[test]
+    fn encode() {
+        let mut blocks = BlockStates::filled(block("minecraft:air"));
+        for i in 0..20 {
+            blocks.set(i % 16, i / 16, 15, block(&format!("minecraft:block_{}", i)));
+        }
+        blocks.set(3, 0, 15, block("minecraft:block_1"));
+        assert_eq!(blocks.palette().len(), 21);
+
+        let tag = blocks.to_tag();
+        assert_eq!(tag["data"].as_long_array().unwrap().len(), 342);
+        let decoded = BlockStates::from_tag(&tag).unwrap();
+        assert_eq!(decoded, blocks);
+        assert_eq!(decoded.get(3, 0, 15), &block("minecraft:block_1"));
+        assert_eq!(decoded.get(3, 1, 15), &block("minecraft:block_19"));
+
+        // the palette does not grow beyond one entry per block
+        let mut blocks = BlockStates::filled(block("minecraft:air"));
+        for i in 0..5000 {
+            let (x, y, z) = (i % 16, (i / 16) % 16, (i / 256) % 16);
+            blocks.set(x, y, z, block(&format!("minecraft:block_{}", i)));
+        }
+        assert!(blocks.palette().len() <= SECTION_VOLUME);
+        assert_eq!(blocks.get(0, 0, 0), &block("minecraft:block_4096"));
+        assert_eq!(blocks.get(15, 15, 15), &block("minecraft:block_4095"));
+        assert_eq!(BlockStates::from_tag(&blocks.to_tag()).unwrap(), blocks);
+    }
+
+    #[test]
+    fn packed() {
+        let mut blocks = BlockStates::filled(block("minecraft:air"));
+        assert_eq!(blocks.to_packed(Packing::Spanning).len(), 256);
+        for i in 0..20 {
+            blocks.set(i % 16, 0, i / 16, block(&format!("minecraft:block_{}", i)));
+        }
+        let data = blocks.to_packed(Packing::Spanning);
+        assert_eq!(data.len(), 320);
+        let decoded =
+            BlockStates::from_packed(blocks.palette().to_vec(), &data, Packing::Spanning).unwrap();
+        assert_eq!(decoded, blocks);
+        assert!(
+            BlockStates::from_packed(blocks.palette().to_vec(), &data, Packing::Padded).is_err()
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use super::packed::{palette_bits, Packing};
+use super::{data_version, set_child, SECTION_WIDTH};
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The amount of bits per entry of heightmaps in worlds up to 511 blocks
+/// high, which includes all worlds before Minecraft 1.18 and the overworld
+/// since.
+pub const HEIGHTMAP_BITS: u32 = 9;
+
+/// The amount of columns in a chunk.
+const COLUMNS: usize = SECTION_WIDTH * SECTION_WIDTH;
+
+/// A heightmap of a chunk such as `MOTION_BLOCKING` or `WORLD_SURFACE`.
+///
+/// Every column of blocks, ordered by z and then x, stores the amount of
+/// blocks from the bottom of the world up to and including the highest block
+/// matching the heightmap, so an empty column has the height zero.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::chunk::Heightmap;
+/// use mcnbt::{Tag, compound, int};
+///
+/// let mut chunk = compound!("" => int!("DataVersion" => 3465));
+/// let mut heightmap = Heightmap::new(384);
+/// heightmap.set(3, 4, 129);
+/// heightmap.write_to_chunk(&mut chunk, "WORLD_SURFACE").unwrap();
+///
+/// assert_eq!(chunk["Heightmaps"]["WORLD_SURFACE"].as_long_array().unwrap().len(), 37);
+/// let heightmap = Heightmap::from_chunk(&chunk, "WORLD_SURFACE", 384).unwrap().unwrap();
+/// assert_eq!(heightmap.get(3, 4), 129);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heightmap {
+    bits: u32,
+    heights: Vec<u16>,
+}
+
+impl Heightmap {
+    /// Creates a heightmap of a world with the given height in blocks where
+    /// every column is empty.
+    pub fn new(height: u32) -> Self {
+        Self {
+            bits: bits_for_height(height),
+            heights: vec![0; COLUMNS],
+        }
+    }
+
+    /// Unpacks a heightmap of a world with the given height in blocks, which
+    /// determines the amount of bits per entry like the game does.
+    pub fn from_packed(data: &[i64], height: u32, packing: Packing) -> Result<Self, Error> {
+        let bits = bits_for_height(height);
+        let heights = packing
+            .unpack(data, bits, COLUMNS)?
+            .into_iter()
+            .map(|height| height as u16)
+            .collect();
+        Ok(Self { bits, heights })
+    }
+
+    /// Reads the heightmap with the given name from a chunk of a world with
+    /// the given height in blocks or returns [`None`] if the chunk does not
+    /// have it.
+    ///
+    /// Heightmaps are stored in `Heightmaps` since Minecraft 1.18 and in
+    /// `Level.Heightmaps` before. Worlds are 256 blocks high before
+    /// Minecraft 1.18 and the overworld is 384 blocks high since.
+    pub fn from_chunk(chunk: &Tag, name: &str, height: u32) -> Result<Option<Self>, Error> {
+        let heightmaps = match chunk.get("Level") {
+            Some(level) => level.get("Heightmaps"),
+            None => chunk.get("Heightmaps"),
+        };
+        let Some(heightmap) = heightmaps.and_then(|heightmaps| heightmaps.get(name)) else {
+            return Ok(None);
+        };
+        let data = heightmap.as_long_array().ok_or(Error::UnexpectedType {
+            expected: "TAG_Long_Array",
+            found: heightmap.name(),
+        })?;
+        let packing = Packing::for_data_version(data_version(chunk));
+        Self::from_packed(data, height, packing).map(Some)
+    }
+
+    /// Returns the amount of bits per entry.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the heights of all columns.
+    pub fn heights(&self) -> &[u16] {
+        &self.heights
+    }
+
+    /// Returns the height of the column at the given coordinates within the
+    /// chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn get(&self, x: usize, z: usize) -> u16 {
+        self.heights[index(x, z)]
+    }
+
+    /// Changes the height of the column at the given coordinates within the
+    /// chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16 or the height does not fit
+    /// into the bits per entry.
+    pub fn set(&mut self, x: usize, z: usize, height: u16) {
+        assert!(
+            u32::from(height) >> self.bits == 0,
+            "the height {} does not fit into {} bits",
+            height,
+            self.bits
+        );
+        self.heights[index(x, z)] = height;
+    }
+
+    /// Packs the heightmap.
+    pub fn to_packed(&self, packing: Packing) -> Vec<i64> {
+        let heights: Vec<u32> = self.heights.iter().map(|h| u32::from(*h)).collect();
+        packing.pack(&heights, self.bits)
+    }
+
+    /// Replaces the heightmap with the given name of a chunk.
+    ///
+    /// The `Heightmaps` compound is created if necessary.
+    pub fn write_to_chunk(&self, chunk: &mut Tag, name: &str) -> Result<(), Error> {
+        let data = self.to_packed(Packing::for_data_version(data_version(chunk)));
+        let parent = if chunk.get("Level").is_some() {
+            &mut chunk["Level"]
+        } else {
+            chunk
+        };
+        let found = parent.name();
+        let parent = parent.as_compound_mut().ok_or(Error::UnexpectedType {
+            expected: "TAG_Compound",
+            found,
+        })?;
+        if !parent
+            .iter()
+            .any(|tag| tag.tag_name() == Some("Heightmaps"))
+        {
+            parent.push(Tag::Compound(Some("Heightmaps".to_string()), vec![]));
+        }
+        let heightmaps = parent
+            .iter_mut()
+            .find(|tag| tag.tag_name() == Some("Heightmaps"))
+            .unwrap();
+        let found = heightmaps.name();
+        let heightmaps = heightmaps.as_compound_mut().ok_or(Error::UnexpectedType {
+            expected: "TAG_Compound",
+            found,
+        })?;
+        set_child(heightmaps, Tag::LongArray(Some(name.to_string()), data));
+        Ok(())
+    }
+}
+
+/// Returns the amount of bits per entry of heightmaps of a world with the
+/// given height in blocks, which must store the heights zero up to and
+/// including the height of the world.
+fn bits_for_height(height: u32) -> u32 {
+    palette_bits(height as usize + 1, 1)
+}
+
+/// Returns the index of a column within a chunk ordered by z and x.
+fn index(x: usize, z: usize) -> usize {
+    assert!(
+        x < SECTION_WIDTH && z < SECTION_WIDTH,
+        "the coordinates {}, {} are outside of the chunk",
+        x,
+        z
+    );
+    z * SECTION_WIDTH + x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compound, int};
+
+    #[test]
+    fn packing() {
+        assert_eq!(bits_for_height(256), HEIGHTMAP_BITS);
+        assert_eq!(bits_for_height(384), HEIGHTMAP_BITS);
+        assert_eq!(bits_for_height(4064), 12);
+
+        let mut heightmap = Heightmap::new(384);
+        for i in 0..256 {
+            heightmap.set(i % 16, i / 16, i as u16 + 100);
+        }
+        let data = heightmap.to_packed(Packing::Spanning);
+        assert_eq!(data.len(), 36);
+        assert_eq!(
+            Heightmap::from_packed(&data, 256, Packing::Spanning).unwrap(),
+            heightmap
+        );
+        let data = heightmap.to_packed(Packing::Padded);
+        assert_eq!(data.len(), 37);
+        assert_eq!(data[0] & 0x1ff, 100);
+        assert_eq!(
+            Heightmap::from_packed(&data, 384, Packing::Padded).unwrap(),
+            heightmap
+        );
+        assert!(matches!(
+            Heightmap::from_packed(&data, 1024, Packing::Padded),
+            Err(Error::InvalidChunkData(_))
+        ));
+    }
+
+    #[test]
+    fn tall() {
+        // 11 and 12 bits both need 52 longs
+        let mut heightmap = Heightmap::new(4064);
+        heightmap.set(0, 0, 3000);
+        heightmap.set(15, 15, 4064);
+        let data = heightmap.to_packed(Packing::Padded);
+        assert_eq!(data.len(), 52);
+        let decoded = Heightmap::from_packed(&data, 4064, Packing::Padded).unwrap();
+        assert_eq!(decoded.bits(), 12);
+        assert_eq!(decoded.get(0, 0), 3000);
+        assert_eq!(decoded.get(15, 15), 4064);
+    }
+
+    #[test]
+    fn chunk() {
+        let mut chunk = compound!("" =>
+            int!("DataVersion" => 1976),
+            compound!("Level" => int!("xPos" => 0)),
+        );
+        assert_eq!(
+            Heightmap::from_chunk(&chunk, "OCEAN_FLOOR", 256).unwrap(),
+            None
+        );
+        let mut heightmap = Heightmap::new(256);
+        heightmap.set(15, 15, 64);
+        heightmap.write_to_chunk(&mut chunk, "OCEAN_FLOOR").unwrap();
+        heightmap.write_to_chunk(&mut chunk, "OCEAN_FLOOR").unwrap();
+
+        let heightmaps = chunk["Level"]["Heightmaps"].as_compound().unwrap();
+        assert_eq!(heightmaps.len(), 1);
+        assert_eq!(heightmaps[0].as_long_array().unwrap().len(), 36);
+        assert_eq!(
+            Heightmap::from_chunk(&chunk, "OCEAN_FLOOR", 256).unwrap(),
+            Some(heightmap)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use super::packed::{pack_nibbles, unpack_nibbles};
+use super::{index, SECTION_VOLUME};
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The numeric block IDs and data values of a section of a chunk saved
+/// before Minecraft 1.13.
+///
+/// The lower eight bits of an ID are stored in `Blocks`, the upper four bits
+/// in the optional nibble array `Add` and the data values in the nibble
+/// array `Data`. Blocks are ordered by their y, z and then x coordinate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LegacyBlocks {
+    ids: Vec<u16>,
+    data: Vec<u8>,
+}
+
+/// Reads a byte array of the section with the given length.
+fn byte_array<'a>(section: &'a Tag, name: &str, len: usize) -> Result<Option<&'a [i8]>, Error> {
+    let Some(tag) = section.get(name) else {
+        return Ok(None);
+    };
+    let array = tag.as_byte_array().ok_or(Error::UnexpectedType {
+        expected: "TAG_Byte_Array",
+        found: tag.name(),
+    })?;
+    if array.len() != len {
+        return Err(Error::InvalidChunkData(format!(
+            "expected {} bytes in {} but found {}",
+            len,
+            name,
+            array.len()
+        )));
+    }
+    Ok(Some(array))
+}
+
+impl LegacyBlocks {
+    /// Creates a section filled with air.
+    pub fn new() -> Self {
+        Self {
+            ids: vec![0; SECTION_VOLUME],
+            data: vec![0; SECTION_VOLUME],
+        }
+    }
+
+    /// Reads the `Blocks`, `Data` and `Add` arrays of a section.
+    pub fn from_section(section: &Tag) -> Result<Self, Error> {
+        let blocks = byte_array(section, "Blocks", SECTION_VOLUME)?
+            .ok_or_else(|| Error::MissingTag("Blocks".to_string()))?;
+        let data = match byte_array(section, "Data", SECTION_VOLUME / 2)? {
+            Some(data) => unpack_nibbles(data),
+            None => vec![0; SECTION_VOLUME],
+        };
+        let add = match byte_array(section, "Add", SECTION_VOLUME / 2)? {
+            Some(add) => unpack_nibbles(add),
+            None => vec![0; SECTION_VOLUME],
+        };
+        let ids = blocks
+            .iter()
+            .zip(add)
+            .map(|(low, high)| u16::from(*low as u8) | u16::from(high) << 8)
+            .collect();
+        Ok(Self { ids, data })
+    }
+
+    /// Returns the IDs of all blocks.
+    pub fn ids(&self) -> &[u16] {
+        &self.ids
+    }
+
+    /// Returns the data values of all blocks.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the ID and the data value of the block at the given
+    /// coordinates within the section.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> (u16, u8) {
+        let i = index(x, y, z);
+        (self.ids[i], self.data[i])
+    }
+
+    /// Changes the block at the given coordinates within the section. IDs
+    /// are truncated to twelve and data values to four bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, id: u16, data: u8) {
+        let i = index(x, y, z);
+        self.ids[i] = id & 0xfff;
+        self.data[i] = data & 0xf;
+    }
+
+    /// Returns the `Blocks`, `Data` and, if any ID exceeds 255, `Add` arrays
+    /// of a section.
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let blocks = self.ids.iter().map(|id| *id as u8 as i8).collect();
+        let mut tags = vec![
+            Tag::ByteArray(Some("Blocks".to_string()), blocks),
+            Tag::ByteArray(Some("Data".to_string()), pack_nibbles(&self.data)),
+        ];
+        if self.ids.iter().any(|id| *id > 0xff) {
+            let add: Vec<u8> = self.ids.iter().map(|id| (id >> 8) as u8).collect();
+            tags.push(Tag::ByteArray(Some("Add".to_string()), pack_nibbles(&add)));
+        }
+        tags
+    }
+}
+
+impl Default for LegacyBlocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound};
+
+    #[test]
+    fn round_trip() {
+        let mut blocks = LegacyBlocks::new();
+        blocks.set(1, 0, 0, 1, 0);
+        blocks.set(0, 0, 0, 35, 14);
+        blocks.set(15, 15, 15, 0x1ff, 3);
+        assert_eq!(blocks.get(0, 0, 0), (35, 14));
+
+        let tags = blocks.to_tags();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[0].as_byte_array().unwrap()[..2], [35, 1]);
+        assert_eq!(tags[1].as_byte_array().unwrap()[0], 14);
+        assert_eq!(tags[2].as_byte_array().unwrap()[2047], 0x10);
+
+        let mut section = compound!(byte!("Y" => 2));
+        section.as_compound_mut().unwrap().extend(tags);
+        let decoded = LegacyBlocks::from_section(&section).unwrap();
+        assert_eq!(decoded, blocks);
+        assert_eq!(decoded.get(15, 15, 15), (0x1ff, 3));
+    }
+
+    #[test]
+    fn invalid() {
+        let section = compound!(Tag::ByteArray(Some("Blocks".to_string()), vec![0; 10]));
+        assert!(matches!(
+            LegacyBlocks::from_section(&section),
+            Err(Error::InvalidChunkData(_))
+        ));
+        assert!(matches!(
+            LegacyBlocks::from_section(&compound!()),
+            Err(Error::MissingTag(_))
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use super::packed::{pack_nibbles, unpack_nibbles};
+use super::{index, set_child, SECTION_VOLUME};
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The kind of light stored in a section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LightLayer {
+    /// The light of the sky stored in `SkyLight`.
+    Sky,
+    /// The light emitted by blocks stored in `BlockLight`.
+    Block,
+}
+
+impl LightLayer {
+    /// Returns the name of the array storing the light in a section.
+    pub fn name(self) -> &'static str {
+        match self {
+            LightLayer::Sky => "SkyLight",
+            LightLayer::Block => "BlockLight",
+        }
+    }
+}
+
+/// The light levels from 0 to 15 of a section of 16×16×16 blocks.
+///
+/// Sections store them as nibble arrays of 2048 bytes where blocks are
+/// ordered by their y, z and then x coordinate.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::chunk::{Light, LightLayer};
+/// use mcnbt::{Tag, byte, compound};
+///
+/// let mut section = compound!(byte!("Y" => 0));
+/// let mut light = Light::new();
+/// light.set(0, 15, 0, 15);
+/// light.write_to_section(&mut section, LightLayer::Block).unwrap();
+///
+/// let light = Light::from_section(&section, LightLayer::Block).unwrap().unwrap();
+/// assert_eq!(light.get(0, 15, 0), 15);
+/// assert_eq!(Light::from_section(&section, LightLayer::Sky).unwrap(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Light {
+    levels: Vec<u8>,
+}
+
+impl Light {
+    /// Creates a section without light.
+    pub fn new() -> Self {
+        Self {
+            levels: vec![0; SECTION_VOLUME],
+        }
+    }
+
+    /// Reads the light of a section or returns [`None`] if the section does
+    /// not store it, which happens if the light has not been computed or the
+    /// section is completely dark or lit.
+    pub fn from_section(section: &Tag, layer: LightLayer) -> Result<Option<Self>, Error> {
+        let Some(tag) = section.get(layer.name()) else {
+            return Ok(None);
+        };
+        let bytes = tag.as_byte_array().ok_or(Error::UnexpectedType {
+            expected: "TAG_Byte_Array",
+            found: tag.name(),
+        })?;
+        if bytes.len() != SECTION_VOLUME / 2 {
+            return Err(Error::InvalidChunkData(format!(
+                "expected {} bytes in {} but found {}",
+                SECTION_VOLUME / 2,
+                layer.name(),
+                bytes.len()
+            )));
+        }
+        Ok(Some(Self {
+            levels: unpack_nibbles(bytes),
+        }))
+    }
+
+    /// Reads the light of all sections of a chunk storing it together with
+    /// the y coordinate of their section.
+    pub fn from_chunk(chunk: &Tag, layer: LightLayer) -> Result<Vec<(i32, Self)>, Error> {
+        let mut sections = vec![];
+        for section in super::sections(chunk) {
+            let Some(light) = Self::from_section(section, layer)? else {
+                continue;
+            };
+            let y = section
+                .get("Y")
+                .and_then(Tag::as_i64_lossy)
+                .ok_or_else(|| Error::MissingTag("Y".to_string()))?;
+            sections.push((y as i32, light));
+        }
+        Ok(sections)
+    }
+
+    /// Returns the light levels of all blocks.
+    pub fn levels(&self) -> &[u8] {
+        &self.levels
+    }
+
+    /// Returns the light level of the block at the given coordinates within
+    /// the section.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.levels[index(x, y, z)]
+    }
+
+    /// Changes the light level of the block at the given coordinates within
+    /// the section. Levels are truncated to four bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is not less than 16.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, level: u8) {
+        self.levels[index(x, y, z)] = level & 0xf;
+    }
+
+    /// Returns the nibble array storing the light in a section.
+    pub fn to_tag(&self, layer: LightLayer) -> Tag {
+        Tag::ByteArray(Some(layer.name().to_string()), pack_nibbles(&self.levels))
+    }
+
+    /// Replaces the light of a section.
+    pub fn write_to_section(&self, section: &mut Tag, layer: LightLayer) -> Result<(), Error> {
+        let found = section.name();
+        let section = section.as_compound_mut().ok_or(Error::UnexpectedType {
+            expected: "TAG_Compound",
+            found,
+        })?;
+        set_child(section, self.to_tag(layer));
+        Ok(())
+    }
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, list};
+
+    #[test]
+    fn round_trip() {
+        let mut light = Light::new();
+        light.set(0, 0, 0, 3);
+        light.set(1, 0, 0, 0x1c);
+        let tag = light.to_tag(LightLayer::Sky);
+        assert_eq!(tag.as_byte_array().unwrap()[0], 0xc3_u8 as i8);
+
+        let chunk = compound!("" => list!("sections" =>
+            compound!(byte!("Y" => -1), tag),
+            compound!(byte!("Y" => 0)),
+        ));
+        let sections = Light::from_chunk(&chunk, LightLayer::Sky).unwrap();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].0, -1);
+        assert_eq!(sections[0].1.get(1, 0, 0), 0xc);
+        assert!(Light::from_chunk(&chunk, LightLayer::Block)
+            .unwrap()
+            .is_empty());
+
+        let invalid = compound!(Tag::ByteArray(Some("BlockLight".to_string()), vec![0; 10]));
+        assert!(matches!(
+            Light::from_section(&invalid, LightLayer::Block),
+            Err(Error::InvalidChunkData(_))
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+//! Packing of small unsigned integers into longs and bytes.
+//!
+//! Since Minecraft 1.16, entries never span across two longs and the unused
+//! upper bits of each long are zero. Before, entries were packed without gaps
+//! and could span across two longs. The free functions of this module use
+//! the former [`Packing::Padded`].
+
+use crate::errors::Error;
+
+/// The first data version whose entries do not span across two longs
+/// (20w17a).
+pub const PADDED_DATA_VERSION: i32 = 2529;
+
+/// How entries are packed into longs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Packing {
+    /// Entries never span across two longs, which is used since Minecraft
+    /// 1.16.
+    Padded,
+    /// Entries are packed without gaps and may span across two longs.
+    Spanning,
+}
+
+impl Packing {
+    /// Returns the packing used by chunks with the given data version.
+    pub fn for_data_version(data_version: i32) -> Self {
+        if data_version >= PADDED_DATA_VERSION {
+            Packing::Padded
+        } else {
+            Packing::Spanning
+        }
+    }
+
+    /// Returns the amount of longs needed to store `len` entries of `bits`
+    /// bits each.
+    pub fn packed_len(self, len: usize, bits: u32) -> usize {
+        match self {
+            Packing::Padded => packed_len(len, bits),
+            Packing::Spanning => (len * bits as usize).div_ceil(64),
+        }
+    }
+
+    /// Unpacks `len` entries of `bits` bits each.
+    pub fn unpack(self, data: &[i64], bits: u32, len: usize) -> Result<Vec<u32>, Error> {
+        if self == Packing::Padded {
+            return unpack(data, bits, len);
+        }
+        check(data, self.packed_len(len, bits), bits)?;
+        if bits == 0 {
+            return Ok(vec![0; len]);
+        }
+
+        let mask = (1_u64 << bits) - 1;
+        Ok((0..len)
+            .map(|i| {
+                let bit = i * bits as usize;
+                let (long, offset) = (bit / 64, (bit % 64) as u32);
+                let mut value = data[long] as u64 >> offset;
+                if offset + bits > 64 {
+                    value |= (data[long + 1] as u64) << (64 - offset);
+                }
+                (value & mask) as u32
+            })
+            .collect())
+    }
+
+    /// Packs entries of `bits` bits each. Larger entries are truncated.
+    pub fn pack(self, values: &[u32], bits: u32) -> Vec<i64> {
+        if self == Packing::Padded || bits == 0 {
+            return pack(values, bits);
+        }
+
+        let mask = (1_u64 << bits) - 1;
+        let mut data = vec![0_u64; self.packed_len(values.len(), bits)];
+        for (i, value) in values.iter().enumerate() {
+            let value = u64::from(*value) & mask;
+            let bit = i * bits as usize;
+            let (long, offset) = (bit / 64, (bit % 64) as u32);
+            data[long] |= value << offset;
+            if offset + bits > 64 {
+                data[long + 1] |= value >> (64 - offset);
+            }
+        }
+        data.into_iter().map(|long| long as i64).collect()
+    }
+}
+
+/// Returns an error if the amount of bits or longs is invalid.
+fn check(data: &[i64], expected: usize, bits: u32) -> Result<(), Error> {
+    if bits > 32 {
+        return Err(Error::InvalidChunkData(format!(
+            "{} bits per entry are not supported",
+            bits
+        )));
+    }
+    if data.len() != expected {
+        return Err(Error::InvalidChunkData(format!(
+            "expected {} longs but found {}",
+            expected,
+            data.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the amount of bits needed to index a palette with the given
+/// length but at least `min`.
+///
+/// A palette with a single entry needs no bits at all.
+pub fn palette_bits(len: usize, min: u32) -> u32 {
+    if len <= 1 {
+        0
+    } else {
+        (usize::BITS - (len - 1).leading_zeros()).max(min)
+    }
+}
+
+/// Returns the amount of longs needed to store `len` entries of `bits` bits
+/// each.
+pub fn packed_len(len: usize, bits: u32) -> usize {
+    if bits == 0 {
+        0
+    } else {
+        len.div_ceil(64 / bits as usize)
+    }
+}
+
+/// Unpacks `len` entries of `bits` bits each.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::chunk::packed::{pack, unpack};
+///
+/// let values = [1, 2, 3, 4, 5];
+/// let data = pack(&values, 5);
+/// assert_eq!(data.len(), 1);
+/// assert_eq!(unpack(&data, 5, values.len()).unwrap(), values);
+/// ```
+pub fn unpack(data: &[i64], bits: u32, len: usize) -> Result<Vec<u32>, Error> {
+    check(data, packed_len(len, bits), bits)?;
+    if bits == 0 {
+        return Ok(vec![0; len]);
+    }
+
+    let per_long = 64 / bits as usize;
+    let mask = (1_u64 << bits) - 1;
+    Ok((0..len)
+        .map(|i| {
+            let long = data[i / per_long] as u64;
+            (long >> ((i % per_long) as u32 * bits) & mask) as u32
+        })
+        .collect())
+}
+
+/// Packs entries of `bits` bits each. Larger entries are truncated.
+pub fn pack(values: &[u32], bits: u32) -> Vec<i64> {
+    if bits == 0 {
+        return vec![];
+    }
+    let per_long = 64 / bits as usize;
+    let mask = (1_u64 << bits) - 1;
+    values
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk.iter().enumerate().fold(0, |long, (i, value)| {
+                long | (u64::from(*value) & mask) << (i as u32 * bits)
+            }) as i64
+        })
+        .collect()
+}
+
+/// Unpacks an array of nibbles where the lower half of each byte comes
+/// first.
+pub fn unpack_nibbles(data: &[i8]) -> Vec<u8> {
+    data.iter()
+        .flat_map(|byte| [*byte as u8 & 0xf, *byte as u8 >> 4])
+        .collect()
+}
+
+/// Packs nibbles where the lower half of each byte comes first. Larger
+/// values are truncated and an odd nibble is padded with zero.
+pub fn pack_nibbles(values: &[u8]) -> Vec<i8> {
+    values
+        .chunks(2)
+        .map(|pair| (pair[0] & 0xf | pair.get(1).map_or(0, |high| high << 4)) as i8)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bits() {
+        assert_eq!(palette_bits(0, 4), 0);
+        assert_eq!(palette_bits(1, 4), 0);
+        assert_eq!(palette_bits(2, 4), 4);
+        assert_eq!(palette_bits(2, 1), 1);
+        assert_eq!(palette_bits(17, 4), 5);
+        assert_eq!(palette_bits(32, 4), 5);
+        assert_eq!(packed_len(4096, 5), 342);
+        assert_eq!(packed_len(4096, 4), 256);
+    }
+
+    #[test]
+    fn round_trip() {
+        let values: Vec<u32> = (0..4096).map(|i| i % 23).collect();
+        let data = pack(&values, 5);
+        assert_eq!(data.len(), 342);
+        // twelve entries per long, the upper four bits stay unused
+        assert_eq!(data[0] as u64 >> 60, 0);
+        assert_eq!(unpack(&data, 5, 4096).unwrap(), values);
+
+        assert_eq!(pack(&[1, 1], 32), [0x1_0000_0001]);
+        assert_eq!(unpack(&[-1], 32, 2).unwrap(), [u32::MAX, u32::MAX]);
+        assert!(unpack(&data[1..], 5, 4096).is_err());
+    }
+
+    #[test]
+    fn spanning() {
+        let values: Vec<u32> = (0..4096).map(|i| i % 23).collect();
+        let data = Packing::Spanning.pack(&values, 5);
+        assert_eq!(data.len(), 320);
+        // the thirteenth entry starts in the first long
+        assert_eq!(data[0] as u64 >> 60, 12 & 0xf);
+        assert_eq!(data[1] & 1, 12 >> 4);
+        assert_eq!(Packing::Spanning.unpack(&data, 5, 4096).unwrap(), values);
+        assert!(Packing::Spanning.unpack(&data, 4, 4096).is_err());
+
+        assert_eq!(Packing::for_data_version(1976), Packing::Spanning);
+        assert_eq!(Packing::for_data_version(2586), Packing::Padded);
+        assert_eq!(Packing::Padded.pack(&values, 5), pack(&values, 5),);
+    }
+
+    #[test]
+    fn nibbles() {
+        assert_eq!(pack_nibbles(&[1, 2, 15]), [0x21, 0x0f]);
+        assert_eq!(unpack_nibbles(&[0x21, -1]), [1, 2, 15, 15]);
+    }
+}
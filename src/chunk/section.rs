@@ -0,0 +1,170 @@
+use super::packed::Packing;
+use super::{BlockStates, LegacyBlocks};
+use crate::convert::FromTag;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The first data version storing block states in palettes (17w47a).
+pub const FLATTENING_DATA_VERSION: i32 = 1451;
+
+/// The first data version storing block states in a `block_states` compound
+/// (21w37a).
+pub const BLOCK_STATES_DATA_VERSION: i32 = 2834;
+
+/// The blocks of a section in any layout used by the game.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SectionBlocks {
+    /// Block states in a palette, used since Minecraft 1.13.
+    Palette(BlockStates),
+    /// Numeric IDs and data values, used before Minecraft 1.13.
+    Legacy(LegacyBlocks),
+}
+
+impl SectionBlocks {
+    /// Reads the blocks of a section of a chunk with the given data version
+    /// or returns [`None`] if the section has no blocks, which happens for
+    /// sections only storing light.
+    ///
+    /// Chunks saved before Minecraft 1.13 store numeric IDs. Later, the
+    /// `Palette` and the `BlockStates` are stored directly in the section
+    /// with indices spanning across longs until Minecraft 1.16. Since
+    /// Minecraft 1.18, they are stored in the `block_states` compound.
+    pub fn from_section(section: &Tag, data_version: i32) -> Result<Option<Self>, Error> {
+        if data_version < FLATTENING_DATA_VERSION {
+            if section.get("Blocks").is_none() {
+                return Ok(None);
+            }
+            return LegacyBlocks::from_section(section).map(|blocks| Some(Self::Legacy(blocks)));
+        }
+        if let Some(block_states) = section.get("block_states") {
+            return BlockStates::from_tag(block_states).map(|blocks| Some(Self::Palette(blocks)));
+        }
+        let Some(palette) = section.get("Palette") else {
+            return Ok(None);
+        };
+        let data = section
+            .get("BlockStates")
+            .ok_or_else(|| Error::MissingTag("BlockStates".to_string()))?;
+        let data = data.as_long_array().ok_or(Error::UnexpectedType {
+            expected: "TAG_Long_Array",
+            found: data.name(),
+        })?;
+        let packing = Packing::for_data_version(data_version);
+        BlockStates::from_packed(Vec::from_tag(palette)?, data, packing)
+            .map(|blocks| Some(Self::Palette(blocks)))
+    }
+
+    /// Reads the blocks of all sections of a chunk together with the y
+    /// coordinate of their section.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::chunk::{LegacyBlocks, SectionBlocks};
+    /// use mcnbt::{Tag, byte, compound, list};
+    ///
+    /// let mut section = compound!(byte!("Y" => 4));
+    /// section.as_compound_mut().unwrap().extend(LegacyBlocks::new().to_tags());
+    /// let chunk = compound!("" => compound!("Level" => list!("Sections" => section)));
+    ///
+    /// let sections = SectionBlocks::from_chunk(&chunk).unwrap();
+    /// assert_eq!(sections[0].0, 4);
+    /// assert!(matches!(sections[0].1, SectionBlocks::Legacy(_)));
+    /// ```
+    pub fn from_chunk(chunk: &Tag) -> Result<Vec<(i32, Self)>, Error> {
+        let data_version = super::data_version(chunk);
+        let mut sections = vec![];
+        for section in super::sections(chunk) {
+            let y = section
+                .get("Y")
+                .and_then(Tag::as_i64_lossy)
+                .ok_or_else(|| Error::MissingTag("Y".to_string()))?;
+            if let Some(blocks) = Self::from_section(section, data_version)? {
+                sections.push((y as i32, blocks));
+            }
+        }
+        Ok(sections)
+    }
+
+    /// Returns the tags storing the blocks in a section of a chunk with the
+    /// given data version.
+    ///
+    /// Legacy blocks are always stored as `Blocks`, `Data` and `Add`.
+    pub fn to_tags(&self, data_version: i32) -> Vec<Tag> {
+        match self {
+            SectionBlocks::Legacy(blocks) => blocks.to_tags(),
+            SectionBlocks::Palette(blocks) if data_version >= BLOCK_STATES_DATA_VERSION => {
+                vec![blocks.to_tag()]
+            }
+            SectionBlocks::Palette(blocks) => {
+                let packing = Packing::for_data_version(data_version);
+                vec![
+                    Tag::List(Some("Palette".to_string()), blocks.palette().to_vec()),
+                    Tag::LongArray(Some("BlockStates".to_string()), blocks.to_packed(packing)),
+                ]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list, string};
+
+    fn blocks() -> BlockStates {
+        let mut blocks = BlockStates::filled(compound!(string!("Name" => "minecraft:air")));
+        for i in 0..20 {
+            let name = format!("minecraft:block_{}", i);
+            blocks.set(i % 16, 1, i / 16, compound!(string!("Name" => name)));
+        }
+        blocks
+    }
+
+    fn chunk(data_version: i32) -> Tag {
+        let blocks = SectionBlocks::Palette(blocks());
+        let mut section = compound!(byte!("Y" => -1));
+        section
+            .as_compound_mut()
+            .unwrap()
+            .extend(blocks.to_tags(data_version));
+        let light = compound!(byte!("Y" => 0));
+        if data_version >= BLOCK_STATES_DATA_VERSION {
+            compound!("" => int!("DataVersion" => data_version), list!("sections" => section, light))
+        } else {
+            compound!("" =>
+                int!("DataVersion" => data_version),
+                compound!("Level" => list!("Sections" => section, light)),
+            )
+        }
+    }
+
+    #[test]
+    fn versions() {
+        for (data_version, len) in [(1976, 320), (2586, 342), (3465, 342)] {
+            let chunk = chunk(data_version);
+            let sections = SectionBlocks::from_chunk(&chunk).unwrap();
+            assert_eq!(sections, [(-1, SectionBlocks::Palette(blocks()))]);
+
+            let section = &super::super::sections(&chunk)[0];
+            let data = section
+                .get("BlockStates")
+                .or_else(|| section.get("block_states").map(|tag| &tag["data"]))
+                .unwrap();
+            assert_eq!(data.as_long_array().unwrap().len(), len);
+        }
+    }
+
+    #[test]
+    fn legacy() {
+        let mut blocks = LegacyBlocks::new();
+        blocks.set(0, 0, 0, 1, 0);
+        let mut section = compound!(byte!("Y" => 0));
+        section.as_compound_mut().unwrap().extend(blocks.to_tags());
+        let chunk = compound!("" => compound!("Level" => list!("Sections" => section)));
+        assert_eq!(
+            SectionBlocks::from_chunk(&chunk).unwrap(),
+            [(0, SectionBlocks::Legacy(blocks))]
+        );
+    }
+}
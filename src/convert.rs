@@ -0,0 +1,331 @@
+//! Conversions between Rust types and tags.
+//!
+//! These traits are implemented by `#[derive(NbtCompound)]` and are what the
+//! fields of such structs have to implement.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Types which can be read from a tag.
+pub trait FromTag: Sized {
+    /// Reads a value from the tag. The name of the tag is ignored.
+    fn from_tag(tag: &Tag) -> Result<Self, Error>;
+}
+
+/// Types which can be turned into an unnamed tag.
+pub trait IntoTag {
+    /// Turns the value into an unnamed tag.
+    fn into_tag(self) -> Tag;
+}
+
+fn unexpected(expected: &'static str, tag: &Tag) -> Error {
+    Error::UnexpectedType {
+        expected,
+        found: tag.name(),
+    }
+}
+
+macro_rules! impl_scalar {
+    ($type:ty, $accessor:ident, $expected:literal) => {
+        impl FromTag for $type {
+            fn from_tag(tag: &Tag) -> Result<Self, Error> {
+                tag.$accessor().ok_or_else(|| unexpected($expected, tag))
+            }
+        }
+
+        impl IntoTag for $type {
+            fn into_tag(self) -> Tag {
+                self.into()
+            }
+        }
+    };
+}
+
+impl_scalar!(i8, as_byte, "TAG_Byte");
+impl_scalar!(i16, as_short, "TAG_Short");
+impl_scalar!(i32, as_int, "TAG_Int");
+impl_scalar!(i64, as_long, "TAG_Long");
+impl_scalar!(f32, as_float, "TAG_Float");
+impl_scalar!(f64, as_double, "TAG_Double");
+impl_scalar!(bool, as_bool, "TAG_Byte");
+
+impl FromTag for String {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        tag.as_str()
+            .map(String::from)
+            .ok_or_else(|| unexpected("TAG_String", tag))
+    }
+}
+
+impl IntoTag for String {
+    fn into_tag(self) -> Tag {
+        self.into()
+    }
+}
+
+impl IntoTag for &str {
+    fn into_tag(self) -> Tag {
+        self.into()
+    }
+}
+
+impl FromTag for Tag {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        let mut tag = tag.clone();
+        *tag.tag_name_mut() = None;
+        Ok(tag)
+    }
+}
+
+impl IntoTag for Tag {
+    fn into_tag(mut self) -> Tag {
+        *self.tag_name_mut() = None;
+        self
+    }
+}
+
+/// Vectors are stored as a [`Tag::List`]. Use `#[nbt(byte_array)]` and
+/// friends to store them as an array instead.
+impl<T: FromTag> FromTag for Vec<T> {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        tag.as_list()
+            .ok_or_else(|| unexpected("TAG_List", tag))?
+            .iter()
+            .map(T::from_tag)
+            .collect()
+    }
+}
+
+impl<T: IntoTag> IntoTag for Vec<T> {
+    fn into_tag(self) -> Tag {
+        self.into_iter().map(T::into_tag).collect()
+    }
+}
+
+impl<T: FromTag> FromTag for HashMap<String, T> {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        tag.as_compound()
+            .ok_or_else(|| unexpected("TAG_Compound", tag))?
+            .iter()
+            .map(|child| {
+                Ok((
+                    child.tag_name().unwrap_or_default().to_string(),
+                    T::from_tag(child)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl<T: IntoTag> IntoTag for HashMap<String, T> {
+    fn into_tag(self) -> Tag {
+        self.into_iter()
+            .map(|(name, value)| (name, value.into_tag()))
+            .collect()
+    }
+}
+
+impl<T: FromTag> FromTag for BTreeMap<String, T> {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        tag.as_compound()
+            .ok_or_else(|| unexpected("TAG_Compound", tag))?
+            .iter()
+            .map(|child| {
+                Ok((
+                    child.tag_name().unwrap_or_default().to_string(),
+                    T::from_tag(child)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+impl<T: IntoTag> IntoTag for BTreeMap<String, T> {
+    fn into_tag(self) -> Tag {
+        self.into_iter()
+            .map(|(name, value)| (name, value.into_tag()))
+            .collect()
+    }
+}
+
+/// Items used by the code generated by `#[derive(NbtCompound)]`.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub use std::default::Default;
+    pub use std::option::Option::{self, None, Some};
+    pub use std::result::Result::{self, Err, Ok};
+    pub use std::vec::Vec;
+
+    pub fn compound(tag: &Tag) -> Result<&[Tag], Error> {
+        tag.as_compound()
+            .ok_or_else(|| unexpected("TAG_Compound", tag))
+    }
+
+    pub fn missing(name: &str) -> Error {
+        Error::MissingTag(name.to_string())
+    }
+
+    pub fn unknown(tag: &Tag) -> Error {
+        Error::UnknownTag(tag.tag_name().unwrap_or_default().to_string())
+    }
+
+    pub fn named(mut tag: Tag, name: &str) -> Tag {
+        *tag.tag_name_mut() = Some(name.to_string());
+        tag
+    }
+
+    pub fn flatten(tag: Tag) -> Vec<Tag> {
+        match tag {
+            Tag::Compound(_, tags) => tags,
+            tag => vec![tag],
+        }
+    }
+
+    pub fn from_byte_array(tag: &Tag) -> Result<Vec<i8>, Error> {
+        tag.as_byte_array()
+            .map(<[i8]>::to_vec)
+            .ok_or_else(|| unexpected("TAG_Byte_Array", tag))
+    }
+
+    pub fn from_int_array(tag: &Tag) -> Result<Vec<i32>, Error> {
+        tag.as_int_array()
+            .map(<[i32]>::to_vec)
+            .ok_or_else(|| unexpected("TAG_Int_Array", tag))
+    }
+
+    pub fn from_long_array(tag: &Tag) -> Result<Vec<i64>, Error> {
+        tag.as_long_array()
+            .map(<[i64]>::to_vec)
+            .ok_or_else(|| unexpected("TAG_Long_Array", tag))
+    }
+
+    pub fn into_byte_array(value: Vec<i8>) -> Tag {
+        Tag::ByteArray(None, value)
+    }
+
+    pub fn into_int_array(value: Vec<i32>) -> Tag {
+        Tag::IntArray(None, value)
+    }
+
+    pub fn into_long_array(value: Vec<i64>) -> Tag {
+        Tag::LongArray(None, value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list};
+
+    #[test]
+    fn collections() {
+        let tag = list!(int!(1), int!(2));
+        assert_eq!(Vec::<i32>::from_tag(&tag).unwrap(), vec![1, 2]);
+        assert_eq!(vec![1, 2].into_tag(), tag);
+        assert!(Vec::<i8>::from_tag(&tag).is_err());
+
+        let tag = compound!(byte!("a" => 1), byte!("b" => 0));
+        let map = BTreeMap::<String, bool>::from_tag(&tag).unwrap();
+        assert!(map["a"]);
+        assert_eq!(map.into_tag(), tag);
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use super::*;
+        use crate::{long_array, string, NbtCompound};
+
+        #[derive(Debug, Default, PartialEq, NbtCompound)]
+        struct Position {
+            x: i32,
+            z: i32,
+        }
+
+        #[derive(Debug, PartialEq, NbtCompound)]
+        struct Player {
+            #[nbt(rename = "Name")]
+            name: String,
+            #[nbt(long_array)]
+            seeds: Vec<i64>,
+            nickname: Option<String>,
+            #[nbt(default = "default_level")]
+            level: i32,
+            #[nbt(flatten)]
+            position: Position,
+            #[nbt(skip)]
+            cache: Vec<String>,
+        }
+
+        fn default_level() -> i32 {
+            1
+        }
+
+        #[derive(Debug, PartialEq, NbtCompound)]
+        #[nbt(deny_unknown_keys)]
+        struct Strict {
+            value: Option<i8>,
+        }
+
+        #[test]
+        fn round_trip() {
+            let player = Player {
+                name: "Steve".to_string(),
+                seeds: vec![1, 2],
+                nickname: None,
+                level: 3,
+                position: Position { x: 4, z: 5 },
+                cache: vec![],
+            };
+            let tag = Tag::from(player);
+            assert_eq!(
+                tag,
+                compound!(
+                    string!("Name" => "Steve"),
+                    long_array!("seeds" => 1, 2),
+                    int!("level" => 3),
+                    int!("x" => 4),
+                    int!("z" => 5),
+                )
+            );
+            assert_eq!(Player::try_from(&tag).unwrap().level, 3);
+        }
+
+        #[test]
+        fn absent_entries() {
+            let tag = compound!(
+                string!("Name" => "Alex"),
+                long_array!("seeds" =>),
+                string!("nickname" => "A"),
+                int!("x" => 0),
+                int!("z" => 0),
+                byte!("unknown" => 0),
+            );
+            let player = Player::from_tag(&tag).unwrap();
+            assert_eq!(player.nickname.as_deref(), Some("A"));
+            assert_eq!(player.level, 1);
+
+            let tag = compound!(string!("Name" => "Alex"));
+            assert!(matches!(
+                Player::from_tag(&tag),
+                Err(Error::MissingTag(name)) if name == "seeds"
+            ));
+        }
+
+        #[test]
+        fn deny_unknown_keys() {
+            assert_eq!(
+                Strict::from_tag(&compound!(byte!("value" => 1))).unwrap(),
+                Strict { value: Some(1) }
+            );
+            assert!(matches!(
+                Strict::from_tag(&compound!(byte!("other" => 1))),
+                Err(Error::UnknownTag(name)) if name == "other"
+            ));
+            assert!(Strict::from_tag(&int!(1)).is_err());
+        }
+    }
+}
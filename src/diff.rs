@@ -0,0 +1,324 @@
+//! Structural differences between tags.
+
+use std::fmt;
+
+use crate::path::{NbtPath, Segment};
+use crate::tag::Tag;
+
+/// The maximum amount of array elements shown by [`render`].
+const RENDERED_ELEMENTS: usize = 8;
+
+/// A single difference between two tags.
+///
+/// The tags stored in a change are unnamed as their name is part of the
+/// path.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The tag only exists in the second tree.
+    Added { path: NbtPath, value: Tag },
+    /// The tag only exists in the first tree.
+    Removed { path: NbtPath, value: Tag },
+    /// The tag has the same type in both trees but a different value.
+    ///
+    /// Lists and compounds are never changed as a whole; their children are
+    /// compared instead.
+    Changed { path: NbtPath, old: Tag, new: Tag },
+    /// The tag has a different type in both trees.
+    TypeChanged { path: NbtPath, old: Tag, new: Tag },
+}
+
+impl Change {
+    /// Returns the path of the tag which changed.
+    pub fn path(&self) -> &NbtPath {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. }
+            | Change::TypeChanged { path, .. } => path,
+        }
+    }
+}
+
+/// Options for [`diff_with`].
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    /// The largest absolute difference between two floats or doubles which
+    /// are still considered equal.
+    pub float_tolerance: f64,
+}
+
+/// Returns the changes turning `a` into `b`.
+///
+/// The order of the children of compounds is ignored, and so are the names
+/// of `a` and `b` themselves. Lists are compared element by element while
+/// arrays are compared as a whole. NaN equals NaN.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, double, list, string};
+/// use mcnbt::diff::{diff, Change};
+///
+/// let a = compound!(byte!("Count" => 1), list!("Pos" => double!(0.5), double!(64.0)));
+/// let b = compound!(list!("Pos" => double!(0.5), double!(65.0)), string!("id" => "minecraft:stone"));
+///
+/// let changes = diff(&a, &b);
+/// assert_eq!(changes.len(), 3);
+/// assert_eq!(changes[0], Change::Removed {
+///     path: "Count".parse().unwrap(),
+///     value: byte!(1),
+/// });
+/// assert_eq!(changes[1].path().to_string(), "Pos[1]");
+/// ```
+pub fn diff(a: &Tag, b: &Tag) -> Vec<Change> {
+    diff_with(a, b, &DiffOptions::default())
+}
+
+/// Like [`diff`] but with the given options.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, double};
+/// use mcnbt::diff::{diff_with, DiffOptions};
+///
+/// let options = DiffOptions { float_tolerance: 0.01 };
+/// assert!(diff_with(&double!(1.0), &double!(1.001), &options).is_empty());
+/// ```
+pub fn diff_with(a: &Tag, b: &Tag, options: &DiffOptions) -> Vec<Change> {
+    let mut changes = vec![];
+    compare(&mut NbtPath::new(), a, b, options, &mut changes);
+    changes
+}
+
+/// Returns a copy of the tag without a name.
+pub(crate) fn unnamed(tag: &Tag) -> Tag {
+    let mut tag = tag.clone();
+    *tag.tag_name_mut() = None;
+    tag
+}
+
+fn floats_equal(a: f64, b: f64, options: &DiffOptions) -> bool {
+    a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= options.float_tolerance
+}
+
+fn compare(path: &mut NbtPath, a: &Tag, b: &Tag, options: &DiffOptions, changes: &mut Vec<Change>) {
+    // containers and changed types record their changes themselves
+    let equal = match (a, b) {
+        (Tag::Byte(_, x), Tag::Byte(_, y)) => x == y,
+        (Tag::Short(_, x), Tag::Short(_, y)) => x == y,
+        (Tag::Int(_, x), Tag::Int(_, y)) => x == y,
+        (Tag::Long(_, x), Tag::Long(_, y)) => x == y,
+        (Tag::Float(_, x), Tag::Float(_, y)) => floats_equal((*x).into(), (*y).into(), options),
+        (Tag::Double(_, x), Tag::Double(_, y)) => floats_equal(*x, *y, options),
+        (Tag::ByteArray(_, x), Tag::ByteArray(_, y)) => x == y,
+        (Tag::String(_, x), Tag::String(_, y)) => x == y,
+        (Tag::IntArray(_, x), Tag::IntArray(_, y)) => x == y,
+        (Tag::LongArray(_, x), Tag::LongArray(_, y)) => x == y,
+        (Tag::List(_, x), Tag::List(_, y)) => {
+            for (index, (x, y)) in x.iter().zip(y).enumerate() {
+                path.push(Segment::Index(index));
+                compare(path, x, y, options, changes);
+                path.pop();
+            }
+            for (index, value) in x.iter().enumerate().skip(y.len()) {
+                changes.push(Change::Removed {
+                    path: path.index(index),
+                    value: unnamed(value),
+                });
+            }
+            for (index, value) in y.iter().enumerate().skip(x.len()) {
+                changes.push(Change::Added {
+                    path: path.index(index),
+                    value: unnamed(value),
+                });
+            }
+            true
+        }
+        (Tag::Compound(_, x), Tag::Compound(_, y)) => {
+            for child in x {
+                let name = child.tag_name().unwrap_or_default();
+                match b.get(name) {
+                    Some(other) => {
+                        path.push(Segment::Key(name.to_string()));
+                        compare(path, child, other, options, changes);
+                        path.pop();
+                    }
+                    None => changes.push(Change::Removed {
+                        path: path.key(name),
+                        value: unnamed(child),
+                    }),
+                }
+            }
+            for child in y {
+                let name = child.tag_name().unwrap_or_default();
+                if a.get(name).is_none() {
+                    changes.push(Change::Added {
+                        path: path.key(name),
+                        value: unnamed(child),
+                    });
+                }
+            }
+            true
+        }
+        _ => {
+            changes.push(Change::TypeChanged {
+                path: path.clone(),
+                old: unnamed(a),
+                new: unnamed(b),
+            });
+            true
+        }
+    };
+
+    if !equal {
+        changes.push(Change::Changed {
+            path: path.clone(),
+            old: unnamed(a),
+            new: unnamed(b),
+        });
+    }
+}
+
+/// Writes the elements of an array, truncated to [`RENDERED_ELEMENTS`].
+fn write_elements<T: fmt::Display>(f: &mut fmt::Formatter<'_>, elements: &[T]) -> fmt::Result {
+    f.write_str("[")?;
+    for (i, element) in elements.iter().take(RENDERED_ELEMENTS).enumerate() {
+        if i != 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", element)?;
+    }
+    if elements.len() > RENDERED_ELEMENTS {
+        write!(f, ", and {} more", elements.len() - RENDERED_ELEMENTS)?;
+    }
+    f.write_str("]")
+}
+
+/// A single line summary of a tag.
+struct Summary<'a>(&'a Tag);
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.0.name())?;
+        match self.0 {
+            Tag::Byte(_, payload) => write!(f, "{}", payload),
+            Tag::Short(_, payload) => write!(f, "{}", payload),
+            Tag::Int(_, payload) => write!(f, "{}", payload),
+            Tag::Long(_, payload) => write!(f, "{}", payload),
+            Tag::Float(_, payload) => write!(f, "{}", payload),
+            Tag::Double(_, payload) => write!(f, "{}", payload),
+            Tag::ByteArray(_, payload) => write_elements(f, payload),
+            Tag::String(_, payload) => write!(f, "'{}'", payload),
+            Tag::List(_, payload) | Tag::Compound(_, payload) => {
+                write!(f, "with {} entries", payload.len())
+            }
+            Tag::IntArray(_, payload) => write_elements(f, payload),
+            Tag::LongArray(_, payload) => write_elements(f, payload),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path().is_root() {
+            "<root>".to_string()
+        } else {
+            self.path().to_string()
+        };
+        match self {
+            Change::Added { value, .. } => write!(f, "+ {}: {}", path, Summary(value)),
+            Change::Removed { value, .. } => write!(f, "- {}: {}", path, Summary(value)),
+            Change::Changed { old, new, .. } => {
+                write!(f, "~ {}: {} -> {}", path, Summary(old), Summary(new))
+            }
+            Change::TypeChanged { old, new, .. } => {
+                write!(f, "! {}: {} -> {}", path, Summary(old), Summary(new))
+            }
+        }
+    }
+}
+
+/// Returns a human-readable representation of the changes with one change
+/// per line.
+///
+/// Lines start with `+` for added tags, `-` for removed tags, `~` for changed
+/// values and `!` for changed types.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, int};
+/// use mcnbt::diff::{diff, render};
+///
+/// let a = compound!(byte!("Count" => 1), int!("Age" => 5));
+/// let b = compound!(byte!("Count" => 2), byte!("Age" => 5));
+///
+/// assert_eq!(
+///     render(&diff(&a, &b)),
+///     "~ Count: TAG_Byte 1 -> TAG_Byte 2\n! Age: TAG_Int 5 -> TAG_Byte 5"
+/// );
+/// ```
+pub fn render(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(Change::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, float, int_array, list, string};
+
+    #[test]
+    fn identical() {
+        let tag = compound!(
+            list!("a" => compound!(byte!("x" => 1)), compound!()),
+            float!("nan" => f32::NAN),
+            int_array!("ints" => 1, 2),
+        );
+        assert!(diff(&tag, &tag).is_empty());
+    }
+
+    #[test]
+    fn key_order_is_ignored() {
+        let a = compound!(byte!("x" => 1), string!("y" => "a"));
+        let b = compound!("name" => string!("y" => "a"), byte!("x" => 1));
+        assert!(diff(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn lists() {
+        let a = list!(byte!(1), byte!(2), byte!(3));
+        let b = list!(byte!(1), byte!(4));
+        assert_eq!(
+            render(&diff(&a, &b)),
+            "~ [1]: TAG_Byte 2 -> TAG_Byte 4\n- [2]: TAG_Byte 3"
+        );
+        assert_eq!(
+            render(&diff(&b, &a)).lines().last(),
+            Some("+ [2]: TAG_Byte 3")
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let a = compound!(list!("Items" => compound!(compound!("tag" => int_array!("ids" => 1)))));
+        let b =
+            compound!(list!("Items" => compound!(compound!("tag" => int_array!("ids" => 1, 2)))));
+        assert_eq!(
+            render(&diff(&a, &b)),
+            "~ Items[0].tag.ids: TAG_Int_Array [1] -> TAG_Int_Array [1, 2]"
+        );
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(
+            render(&diff(&byte!(1), &string!("1"))),
+            "! <root>: TAG_Byte 1 -> TAG_String '1'"
+        );
+    }
+}
@@ -20,6 +20,52 @@ pub enum Error {
     #[error("the amount of tags of the long array must not exceed {}", i32::MAX)]
     LongArrayTooBig,
 
+    #[error("expected {expected} but found {found}")]
+    UnexpectedType {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    #[error("missing tag \"{0}\" in compound")]
+    MissingTag(String),
+
+    #[error("unknown tag \"{0}\" in compound")]
+    UnknownTag(String),
+
+    #[error("invalid path: {0}")]
+    InvalidPath(String),
+
+    #[error("failed to apply patch at \"{path}\": {reason}")]
+    PatchFailed { path: String, reason: &'static str },
+
+    #[error("invalid region file: {0}")]
+    InvalidRegion(String),
+
+    #[error("chunk {x}, {z} is invalid: {reason}")]
+    InvalidChunk {
+        x: i32,
+        z: i32,
+        reason: &'static str,
+    },
+
+    #[error("invalid chunk data: {0}")]
+    InvalidChunkData(String),
+
+    #[error("unsupported compression type {0}")]
+    UnsupportedCompression(u8),
+
+    #[error("all elements of a list must be of the same type")]
+    HeterogeneousList,
+
+    #[error("the keys of a compound must be strings")]
+    KeyMustBeString,
+
+    #[error("{0}")]
+    Custom(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     // TODO: improve this
     #[error("failed to parse NBT")]
     ParseError(nom::error::ErrorKind),
@@ -27,3 +73,17 @@ pub enum Error {
     #[error("NBT is incomplete")]
     Incomplete(nom::Needed),
 }
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
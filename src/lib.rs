@@ -1,10 +1,30 @@
 #![doc = include_str!("../README.md")]
 
+extern crate self as mcnbt;
+
 pub mod byte_order;
+pub mod canonical;
+pub mod chunk;
+pub mod convert;
+pub mod diff;
 pub mod errors;
+pub mod merge;
 #[cfg(feature = "read")]
 pub(crate) mod parser;
+pub mod patch;
+pub mod path;
+#[cfg(feature = "region")]
+pub mod region;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod tag;
+pub mod walk;
 
+#[cfg(all(feature = "serde", feature = "read"))]
+pub use crate::serde::{from_bytes, from_reader};
+#[cfg(feature = "serde")]
+pub use crate::serde::{to_bytes, to_writer};
 pub use byte_order::ByteOrder;
+#[cfg(feature = "derive")]
+pub use mcnbt_derive::{snbt, NbtCompound};
 pub use tag::Tag;
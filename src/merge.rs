@@ -0,0 +1,231 @@
+//! Recursive merging of tags like the `/data merge` command.
+
+use crate::diff::{diff, unnamed};
+use crate::path::{NbtPath, Segment};
+use crate::tag::Tag;
+
+/// How [`Tag::merge`] combines two lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Replaces the list with the other one like Minecraft does.
+    #[default]
+    Replace,
+    /// Appends the elements of the other list.
+    Append,
+    /// Merges compounds whose child with the given name is equal and appends
+    /// all other elements.
+    MergeByKey(String),
+}
+
+/// A tag which was replaced while merging because it could not be combined
+/// with the other tag.
+///
+/// This happens if the types of the tags differ or if the elements of two
+/// lists have different types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    /// The path of the replaced tag.
+    pub path: NbtPath,
+    /// The replaced tag without its name.
+    pub old: Tag,
+    /// The tag which replaced it without its name.
+    pub new: Tag,
+}
+
+/// Merges `other` into `tag`.
+///
+/// See [`Tag::merge`] for details.
+pub fn merge(tag: &mut Tag, other: &Tag, strategy: &MergeStrategy) -> Vec<Conflict> {
+    let mut conflicts = vec![];
+    merge_into(&mut NbtPath::new(), tag, other, strategy, &mut conflicts);
+    conflicts
+}
+
+/// Replaces the payload of `tag` with the one of `other` but keeps its name.
+///
+/// A conflict is reported if the types of the tags or the element types of
+/// two non-empty lists differ.
+fn replace(path: &NbtPath, tag: &mut Tag, other: &Tag, conflicts: &mut Vec<Conflict>) {
+    let name = tag.tag_name_mut().take();
+    let old = std::mem::replace(tag, other.clone());
+    *tag.tag_name_mut() = name;
+    let elements_differ = match (&old, other) {
+        (Tag::List(_, old), Tag::List(_, new)) => {
+            matches!((element_type(old), element_type(new)), (Some(a), Some(b)) if a != b)
+        }
+        _ => false,
+    };
+    if old.name() != other.name() || elements_differ {
+        conflicts.push(Conflict {
+            path: path.clone(),
+            old: unnamed(&old),
+            new: unnamed(other),
+        });
+    }
+}
+
+/// Returns the element type of a list or [`None`] if it is empty.
+fn element_type(elements: &[Tag]) -> Option<&'static str> {
+    elements.first().map(Tag::name)
+}
+
+fn merge_into(
+    path: &mut NbtPath,
+    tag: &mut Tag,
+    other: &Tag,
+    strategy: &MergeStrategy,
+    conflicts: &mut Vec<Conflict>,
+) {
+    match (tag, other) {
+        (Tag::Compound(_, children), Tag::Compound(_, others)) => {
+            for other in others {
+                let name = other.tag_name().unwrap_or_default();
+                match children
+                    .iter_mut()
+                    .find(|child| child.tag_name() == Some(name))
+                {
+                    Some(child) => {
+                        path.push(Segment::Key(name.to_string()));
+                        merge_into(path, child, other, strategy, conflicts);
+                        path.pop();
+                    }
+                    None => children.push(other.clone()),
+                }
+            }
+        }
+        (Tag::List(_, elements), Tag::List(_, others)) if *strategy != MergeStrategy::Replace => {
+            if let (Some(a), Some(b)) = (element_type(elements), element_type(others)) {
+                if a != b {
+                    // the lists cannot be combined
+                    let old = std::mem::replace(elements, others.clone());
+                    conflicts.push(Conflict {
+                        path: path.clone(),
+                        old: Tag::List(None, old),
+                        new: unnamed(other),
+                    });
+                    return;
+                }
+            }
+            match strategy {
+                MergeStrategy::MergeByKey(key) => {
+                    for other in others {
+                        let position = other.get(key).and_then(|id| {
+                            elements.iter().position(|element| {
+                                element
+                                    .get(key)
+                                    .is_some_and(|value| diff(value, id).is_empty())
+                            })
+                        });
+                        match position {
+                            Some(index) => {
+                                path.push(Segment::Index(index));
+                                merge_into(path, &mut elements[index], other, strategy, conflicts);
+                                path.pop();
+                            }
+                            None => elements.push(other.clone()),
+                        }
+                    }
+                }
+                _ => elements.extend(others.iter().cloned()),
+            }
+        }
+        (tag, other) => replace(path, tag, other, conflicts),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list, string};
+
+    fn items() -> Tag {
+        compound!("root" => list!("Items" =>
+            compound!(string!("id" => "a"), byte!("Count" => 1)),
+            compound!(string!("id" => "b"), byte!("Count" => 2)),
+        ))
+    }
+
+    #[test]
+    fn replace() {
+        let mut tag = items();
+        let other = compound!(
+            list!("Items" => compound!(string!("id" => "c"))),
+            byte!("New" => 1)
+        );
+        assert!(tag.merge(&other, &MergeStrategy::Replace).is_empty());
+        assert_eq!(
+            tag,
+            compound!("root" => list!("Items" => compound!(string!("id" => "c"))), byte!("New" => 1))
+        );
+    }
+
+    #[test]
+    fn append() {
+        let mut tag = items();
+        let other = compound!(list!("Items" => compound!(string!("id" => "a"))));
+        assert!(tag.merge(&other, &MergeStrategy::Append).is_empty());
+        assert_eq!(tag["Items"].as_list().unwrap().len(), 3);
+
+        let other = compound!(list!("Items" => int!(1)));
+        let conflicts = tag.merge(&other, &MergeStrategy::Append);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].old.as_list().unwrap().len(), 3);
+        assert_eq!(tag["Items"], list!("Items" => int!(1)));
+    }
+
+    #[test]
+    fn merge_by_key() {
+        let mut tag = items();
+        let other = compound!(list!("Items" =>
+            compound!(string!("id" => "b"), byte!("Count" => 3), byte!("Slot" => 4)),
+            compound!(string!("id" => "c")),
+            compound!(byte!("Count" => 5)),
+        ));
+        assert!(tag
+            .merge(&other, &MergeStrategy::MergeByKey("id".to_string()))
+            .is_empty());
+        assert_eq!(
+            tag["Items"],
+            list!("Items" =>
+                compound!(string!("id" => "a"), byte!("Count" => 1)),
+                compound!(string!("id" => "b"), byte!("Count" => 3), byte!("Slot" => 4)),
+                compound!(string!("id" => "c")),
+                compound!(byte!("Count" => 5)),
+            )
+        );
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut tag = compound!(compound!("a" => byte!("b" => 1)), int!("c" => 2));
+        let other = compound!(compound!("a" => string!("b" => "x")), compound!("c" =>));
+        let conflicts = tag.merge(&other, &MergeStrategy::Replace);
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    path: "a.b".parse().unwrap(),
+                    old: byte!(1),
+                    new: string!("x"),
+                },
+                Conflict {
+                    path: "c".parse().unwrap(),
+                    old: int!(2),
+                    new: compound!(),
+                },
+            ]
+        );
+        assert_eq!(tag["c"], compound!("c" =>));
+
+        let mut tag = items();
+        let other = compound!(list!("Items" => int!(1)));
+        let conflicts = tag.merge(&other, &MergeStrategy::Replace);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "Items".parse().unwrap());
+        assert_eq!(conflicts[0].old.as_list().unwrap().len(), 2);
+        assert_eq!(tag["Items"], list!("Items" => int!(1)));
+        assert!(tag
+            .merge(&compound!(list!("Items" =>)), &MergeStrategy::Replace)
+            .is_empty());
+    }
+}
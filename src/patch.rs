@@ -0,0 +1,404 @@
+//! Patches describing how to turn one tag into another.
+//!
+//! A [`Patch`] is a list of [`Operation`]s similar to
+//! [JSON Patch](https://www.rfc-editor.org/rfc/rfc6902) but addressing tags
+//! with an [`NbtPath`]. Unlike JSON Patch, removing and replacing a tag
+//! requires the previous value so that every patch can be inverted.
+//!
+//! With the `serde` feature, patches serialize as a sequence of operations,
+//! for example `[{"op": "remove", "path": "Count", "value": ...}]`. Tags
+//! are written with [`Lossless`](crate::serde::Lossless).
+
+use crate::diff::{diff, Change};
+use crate::errors::Error;
+use crate::path::{NbtPath, Segment};
+use crate::tag::Tag;
+
+/// A single step of a [`Patch`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "op", rename_all = "snake_case")
+)]
+pub enum Operation {
+    /// Inserts a tag into a compound or a list. The tag must not exist yet.
+    Add {
+        path: NbtPath,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "lossless"))]
+        value: Tag,
+    },
+    /// Removes the tag which must be equal to `value`.
+    Remove {
+        path: NbtPath,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "lossless"))]
+        value: Tag,
+    },
+    /// Replaces the tag which must be equal to `old` with `value`.
+    Replace {
+        path: NbtPath,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "lossless"))]
+        old: Tag,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "lossless"))]
+        value: Tag,
+    },
+    /// Removes the tag at `from` and adds it at `path`.
+    Move { from: NbtPath, path: NbtPath },
+    /// Fails unless the tag is equal to `value`.
+    Test {
+        path: NbtPath,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "lossless"))]
+        value: Tag,
+    },
+}
+
+#[cfg(feature = "serde")]
+fn lossless<S: serde::Serializer>(tag: &Tag, serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::serde::Lossless(tag), serializer)
+}
+
+impl Operation {
+    /// Returns the operation undoing this one.
+    pub fn invert(&self) -> Operation {
+        match self.clone() {
+            Operation::Add { path, value } => Operation::Remove { path, value },
+            Operation::Remove { path, value } => Operation::Add { path, value },
+            Operation::Replace { path, old, value } => Operation::Replace {
+                path,
+                old: value,
+                value: old,
+            },
+            Operation::Move { from, path } => Operation::Move {
+                from: path,
+                path: from,
+            },
+            test @ Operation::Test { .. } => test,
+        }
+    }
+}
+
+/// A sequence of operations applied in order.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, string};
+/// use mcnbt::patch::Patch;
+///
+/// let old = compound!(byte!("Count" => 1), string!("id" => "minecraft:stone"));
+/// let new = compound!(byte!("Count" => 64), string!("id" => "minecraft:stone"));
+///
+/// let patch = Patch::between(&old, &new);
+/// let mut tag = old.clone();
+/// tag.apply_patch(&patch).unwrap();
+/// assert_eq!(tag, new);
+///
+/// tag.apply_patch(&patch.invert()).unwrap();
+/// assert_eq!(tag, old);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Patch(pub Vec<Operation>);
+
+impl Patch {
+    /// Creates a patch turning `a` into `b` from their [`diff`].
+    ///
+    /// Added children of compounds are appended to them.
+    pub fn between(a: &Tag, b: &Tag) -> Patch {
+        diff(a, b).into()
+    }
+
+    /// Returns the patch undoing this one.
+    pub fn invert(&self) -> Patch {
+        Patch(self.0.iter().rev().map(Operation::invert).collect())
+    }
+
+    /// Applies the operations to the tag.
+    ///
+    /// Either all operations succeed or the tag is left unchanged. Tags are
+    /// compared like [`diff`] does, so the order of the children of
+    /// compounds does not matter.
+    pub fn apply(&self, tag: &mut Tag) -> Result<(), Error> {
+        let mut patched = tag.clone();
+        for operation in &self.0 {
+            apply(&mut patched, operation)?;
+        }
+        *tag = patched;
+        Ok(())
+    }
+}
+
+impl From<Vec<Change>> for Patch {
+    fn from(changes: Vec<Change>) -> Self {
+        let mut operations = vec![];
+        // trailing elements of a list are removed back to front so that the
+        // indices stay valid
+        let mut removed: Vec<Operation> = vec![];
+        for change in changes {
+            let operation = match change {
+                Change::Added { path, value } => Operation::Add { path, value },
+                Change::Removed { path, value } => {
+                    if let Some(Segment::Index(_)) = path.last() {
+                        removed.push(Operation::Remove { path, value });
+                        continue;
+                    }
+                    Operation::Remove { path, value }
+                }
+                Change::Changed { path, old, new } | Change::TypeChanged { path, old, new } => {
+                    Operation::Replace {
+                        path,
+                        old,
+                        value: new,
+                    }
+                }
+            };
+            operations.extend(removed.drain(..).rev());
+            operations.push(operation);
+        }
+        operations.extend(removed.drain(..).rev());
+        Patch(operations)
+    }
+}
+
+impl FromIterator<Operation> for Patch {
+    fn from_iter<I: IntoIterator<Item = Operation>>(iter: I) -> Self {
+        Patch(iter.into_iter().collect())
+    }
+}
+
+fn failed(path: &NbtPath, reason: &'static str) -> Error {
+    Error::PatchFailed {
+        path: path.to_string(),
+        reason,
+    }
+}
+
+/// Returns `true` if the tags are equal apart from their names.
+fn same(a: &Tag, b: &Tag) -> bool {
+    diff(a, b).is_empty()
+}
+
+/// Returns the tag at the path.
+fn get<'a>(tag: &'a Tag, path: &NbtPath) -> Result<&'a Tag, Error> {
+    tag.get_path(path)
+        .ok_or_else(|| failed(path, "tag does not exist"))
+}
+
+/// Returns the parent of the path and the last segment.
+fn parent<'a>(tag: &'a mut Tag, path: &NbtPath) -> Result<(&'a mut Tag, Segment), Error> {
+    let (Some(parent), Some(last)) = (path.parent(), path.last()) else {
+        return Err(failed(path, "the root cannot be added or removed"));
+    };
+    let parent = tag
+        .get_path_mut(&parent)
+        .ok_or_else(|| failed(path, "parent does not exist"))?;
+    Ok((parent, last.clone()))
+}
+
+fn insert(tag: &mut Tag, path: &NbtPath, mut value: Tag) -> Result<(), Error> {
+    let (parent, last) = parent(tag, path)?;
+    match last {
+        Segment::Key(key) => {
+            if parent.get(&key).is_some() {
+                return Err(failed(path, "tag already exists"));
+            }
+            let children = parent
+                .as_compound_mut()
+                .ok_or_else(|| failed(path, "parent is not a compound"))?;
+            *value.tag_name_mut() = Some(key);
+            children.push(value);
+        }
+        Segment::Index(index) => {
+            let elements = parent
+                .as_list_mut()
+                .ok_or_else(|| failed(path, "parent is not a list"))?;
+            if index > elements.len() {
+                return Err(failed(path, "index out of bounds"));
+            }
+            if let Some(first) = elements.first() {
+                if first.name() != value.name() {
+                    return Err(failed(path, "type does not match the list"));
+                }
+            }
+            *value.tag_name_mut() = None;
+            elements.insert(index, value);
+        }
+    }
+    Ok(())
+}
+
+fn remove(tag: &mut Tag, path: &NbtPath) -> Result<Tag, Error> {
+    let (parent, last) = parent(tag, path)?;
+    let elements = match (&last, parent) {
+        (Segment::Key(_), Tag::Compound(_, children)) => children,
+        (Segment::Index(_), Tag::List(_, elements)) => elements,
+        _ => return Err(failed(path, "tag does not exist")),
+    };
+    let index = match last {
+        Segment::Key(key) => elements
+            .iter()
+            .position(|child| child.tag_name() == Some(key.as_str())),
+        Segment::Index(index) => Some(index).filter(|index| *index < elements.len()),
+    };
+    let index = index.ok_or_else(|| failed(path, "tag does not exist"))?;
+    Ok(elements.remove(index))
+}
+
+fn apply(tag: &mut Tag, operation: &Operation) -> Result<(), Error> {
+    match operation {
+        Operation::Add { path, value } => insert(tag, path, value.clone()),
+        Operation::Remove { path, value } => {
+            if !same(get(tag, path)?, value) {
+                return Err(failed(path, "tag differs from the removed value"));
+            }
+            remove(tag, path).map(drop)
+        }
+        Operation::Replace { path, old, value } => {
+            if !same(get(tag, path)?, old) {
+                return Err(failed(path, "tag differs from the replaced value"));
+            }
+            let target = tag.get_path_mut(path).unwrap();
+            let name = target.tag_name_mut().take();
+            *target = value.clone();
+            *target.tag_name_mut() = name;
+            Ok(())
+        }
+        Operation::Move { from, path } => {
+            if path.starts_with(from) && path != from {
+                return Err(failed(path, "a tag cannot be moved into itself"));
+            }
+            let value = remove(tag, from)?;
+            insert(tag, path, value)
+        }
+        Operation::Test { path, value } => {
+            if same(get(tag, path)?, value) {
+                Ok(())
+            } else {
+                Err(failed(path, "test failed"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, double, int, list, long, string};
+
+    fn round_trip(a: &Tag, b: &Tag) {
+        let patch = Patch::between(a, b);
+        let mut tag = a.clone();
+        tag.apply_patch(&patch).unwrap();
+        assert!(diff(&tag, b).is_empty(), "{:?}", patch);
+        tag.apply_patch(&patch.invert()).unwrap();
+        assert!(diff(&tag, a).is_empty(), "{:?}", patch);
+    }
+
+    #[test]
+    fn between() {
+        let a = compound!(
+            list!("Pos" => double!(1.0), double!(2.0), double!(3.0)),
+            list!("Items" => compound!(byte!("Count" => 1)), compound!(byte!("Count" => 2))),
+            int!("Age" => 4),
+        );
+        let b = compound!(
+            list!("Pos" => double!(1.0)),
+            list!("Items" => compound!(byte!("Count" => 1), string!("id" => "stone"))),
+            long!("Age" => 4),
+            byte!("OnGround" => 1),
+        );
+        round_trip(&a, &b);
+        round_trip(&b, &a);
+    }
+
+    #[test]
+    fn move_and_test() {
+        let mut tag = compound!(compound!("a" => int!("x" => 1)), compound!("b"=>));
+        let patch = Patch(vec![
+            Operation::Move {
+                from: "a.x".parse().unwrap(),
+                path: "b.y".parse().unwrap(),
+            },
+            Operation::Test {
+                path: "b.y".parse().unwrap(),
+                value: int!(1),
+            },
+        ]);
+        tag.apply_patch(&patch).unwrap();
+        assert_eq!(tag["b"]["y"], int!("y" => 1));
+        assert_eq!(tag.get_path(&"a.x".parse().unwrap()), None);
+
+        tag.apply_patch(&patch.invert()).unwrap();
+        assert_eq!(tag["a"]["x"], int!("x" => 1));
+    }
+
+    #[test]
+    fn failures_leave_the_tag_unchanged() {
+        let original = compound!(int!("x" => 1), list!("l" => int!(1)));
+        for operation in [
+            Operation::Remove {
+                path: "x".parse().unwrap(),
+                value: int!(2),
+            },
+            Operation::Add {
+                path: "x".parse().unwrap(),
+                value: int!(2),
+            },
+            Operation::Add {
+                path: "l[0]".parse().unwrap(),
+                value: byte!(2),
+            },
+            Operation::Add {
+                path: "l[2]".parse().unwrap(),
+                value: int!(2),
+            },
+            Operation::Test {
+                path: "y".parse().unwrap(),
+                value: int!(1),
+            },
+        ] {
+            let mut tag = original.clone();
+            let patch = Patch(vec![
+                Operation::Add {
+                    path: "z".parse().unwrap(),
+                    value: int!(0),
+                },
+                operation,
+            ]);
+            assert!(tag.apply_patch(&patch).is_err());
+            assert_eq!(tag, original);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let patch = Patch(vec![
+            Operation::Replace {
+                path: "Pos[0]".parse().unwrap(),
+                old: double!(f64::NAN),
+                value: double!(0.5),
+            },
+            Operation::Move {
+                from: "a".parse().unwrap(),
+                path: "\"b c\"".parse().unwrap(),
+            },
+        ]);
+        let json = serde_json::to_value(&patch).unwrap();
+        assert_eq!(json[0]["op"], "replace");
+        assert_eq!(json[0]["path"], "Pos[0]");
+        assert_eq!(json[0]["old"]["payload"], "NaN");
+        assert_eq!(json[1]["path"], "\"b c\"");
+
+        let parsed: Patch = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.0[1], patch.0[1]);
+        assert!(matches!(
+            &parsed.0[0],
+            Operation::Replace { old: Tag::Double(None, old), .. } if old.is_nan()
+        ));
+    }
+}
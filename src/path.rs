@@ -0,0 +1,262 @@
+//! Paths pointing to tags nested within other tags.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::Error;
+
+/// A single step of an [`NbtPath`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Segment {
+    /// The child of a [`Tag::Compound`](crate::Tag::Compound) with the given
+    /// name.
+    Key(String),
+    /// The element of a [`Tag::List`](crate::Tag::List) or an array at the
+    /// given index.
+    Index(usize),
+}
+
+/// A path to a tag in the syntax used by Minecraft commands, for example
+/// `Inventory[0].tag.display.Name`.
+///
+/// Keys containing characters other than ASCII letters, digits, `_`, `-` and
+/// `+` are enclosed in double quotes. The empty path points to the root.
+///
+/// With the `serde` feature, paths serialize as strings.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::path::{NbtPath, Segment};
+///
+/// let path: NbtPath = "Inventory[0].\"custom name\"".parse().unwrap();
+/// assert_eq!(
+///     path.segments(),
+///     &[
+///         Segment::Key("Inventory".to_string()),
+///         Segment::Index(0),
+///         Segment::Key("custom name".to_string()),
+///     ]
+/// );
+/// assert_eq!(path.to_string(), "Inventory[0].\"custom name\"");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NbtPath(Vec<Segment>);
+
+impl NbtPath {
+    /// Creates a path pointing to the root.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the segments of the path.
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+
+    /// Returns `true` if the path points to the root.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the last segment of the path.
+    pub fn last(&self) -> Option<&Segment> {
+        self.0.last()
+    }
+
+    /// Returns the path without its last segment or [`None`] if the path
+    /// points to the root.
+    pub fn parent(&self) -> Option<NbtPath> {
+        let (_, parent) = self.0.split_last()?;
+        Some(NbtPath(parent.to_vec()))
+    }
+
+    /// Appends a segment to the path.
+    pub fn push(&mut self, segment: Segment) {
+        self.0.push(segment);
+    }
+
+    /// Removes the last segment of the path.
+    pub fn pop(&mut self) -> Option<Segment> {
+        self.0.pop()
+    }
+
+    /// Returns a new path with the key appended.
+    pub fn key(&self, key: impl Into<String>) -> NbtPath {
+        let mut path = self.clone();
+        path.push(Segment::Key(key.into()));
+        path
+    }
+
+    /// Returns a new path with the index appended.
+    pub fn index(&self, index: usize) -> NbtPath {
+        let mut path = self.clone();
+        path.push(Segment::Index(index));
+        path
+    }
+
+    /// Returns `true` if this path is `other` or points into it.
+    pub fn starts_with(&self, other: &NbtPath) -> bool {
+        self.0.starts_with(&other.0)
+    }
+}
+
+impl From<Vec<Segment>> for NbtPath {
+    fn from(segments: Vec<Segment>) -> Self {
+        Self(segments)
+    }
+}
+
+impl FromIterator<Segment> for NbtPath {
+    fn from_iter<I: IntoIterator<Item = Segment>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Returns `true` if the character may appear in an unquoted key.
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+')
+}
+
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Key(key) => {
+                    if i != 0 {
+                        f.write_str(".")?;
+                    }
+                    if !key.is_empty() && key.chars().all(is_unquoted) {
+                        f.write_str(key)?;
+                    } else {
+                        f.write_str("\"")?;
+                        for c in key.chars() {
+                            if matches!(c, '"' | '\\') {
+                                f.write_str("\\")?;
+                            }
+                            write!(f, "{}", c)?;
+                        }
+                        f.write_str("\"")?;
+                    }
+                }
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NbtPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| Error::InvalidPath(format!("{} in \"{}\"", reason, s));
+
+        let mut segments = vec![];
+        let mut chars = s.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '[' => {
+                    chars.next();
+                    let mut digits = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) if c.is_ascii_digit() => digits.push(c),
+                            _ => return Err(invalid("expected an index")),
+                        }
+                    }
+                    let index = digits.parse().map_err(|_| invalid("expected an index"))?;
+                    segments.push(Segment::Index(index));
+                }
+                '.' if !segments.is_empty() => {
+                    chars.next();
+                    if matches!(chars.peek(), None | Some('.' | '[')) {
+                        return Err(invalid("expected a key after `.`"));
+                    }
+                    segments.push(Segment::Key(parse_key(&mut chars, &invalid)?));
+                }
+                _ if segments.is_empty() => {
+                    segments.push(Segment::Key(parse_key(&mut chars, &invalid)?));
+                }
+                _ => return Err(invalid("expected `.` or `[`")),
+            }
+        }
+        Ok(Self(segments))
+    }
+}
+
+fn parse_key(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    invalid: &dyn Fn(&str) -> Error,
+) -> Result<String, Error> {
+    let mut key = String::new();
+    if chars.peek() == Some(&'"') {
+        chars.next();
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(key),
+                Some('\\') => match chars.next() {
+                    Some(c @ ('"' | '\\')) => key.push(c),
+                    _ => return Err(invalid("invalid escape sequence")),
+                },
+                Some(c) => key.push(c),
+                None => return Err(invalid("unterminated quoted key")),
+            }
+        }
+    }
+
+    while let Some(&c) = chars.peek() {
+        if !is_unquoted(c) {
+            break;
+        }
+        key.push(c);
+        chars.next();
+    }
+    if key.is_empty() {
+        return Err(invalid("expected a key"));
+    }
+    Ok(key)
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NbtPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NbtPath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        path.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for path in [
+            "",
+            "a",
+            "[3]",
+            "[0][1]",
+            "Inventory[0].tag.display.Name",
+            "\"\"",
+            "\"a.b\".\"quote\\\"d\"[2]",
+        ] {
+            assert_eq!(path.parse::<NbtPath>().unwrap().to_string(), path);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for path in ["a.", "a..b", "a[", "a[-1]", "a[x]", "\"a", "a b", ".a"] {
+            assert!(path.parse::<NbtPath>().is_err(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Region files storing the chunks of Java Edition worlds.
+//!
+//! A region file (`r.<x>.<z>.mca`) contains up to 32×32 chunks. It starts
+//! with a table of 1024 locations followed by a table of 1024 timestamps,
+//! each occupying one sector of 4 KiB. A location consists of the offset of
+//! the chunk in sectors and the amount of sectors it occupies. Every chunk
+//! starts with its length and its [`Compression`] followed by the compressed
+//! NBT.
+//!
+//! Chunks are addressed by their coordinates modulo 32, so both the
+//! coordinates of a chunk within the region and its absolute coordinates can
+//! be used.
+//!
+//! McRegion files (`r.<x>.<z>.mcr`) used before Minecraft 1.2 share the
+//! same layout and can be read the same way. Worlds of Minecraft Alpha,
+//! which store every chunk in its own file, can be read with [`AlphaWorld`].
+//!
+//! The region files of the `region`, `entities` and `poi` folders of a world
+//! can be accessed together with [`RegionSet`], which also reads the
+//! entities and points of interest of chunks.
+//!
+//! Regions opened with [`Region::open_writable`] or created from a writable
+//! buffer can also be modified.
+//!
+//! Chunks too large for the region file are stored in a separate
+//! `c.<x>.<z>.mcc` file next to it, where `x` and `z` are the absolute
+//! coordinates of the chunk. These files are read and written transparently
+//! if the region was opened from a path.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use mcnbt::region::Region;
+//!
+//! let mut region = Region::open("world/region/r.0.0.mca").unwrap();
+//! for info in region.chunks().collect::<Vec<_>>() {
+//!     let chunk = region.read_chunk(info.x, info.z).unwrap().unwrap();
+//!     println!("{}, {}: {}", info.x, info.z, chunk["DataVersion"].pretty());
+//! }
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::Error;
+
+mod alpha;
+mod check;
+mod compression;
+mod entity;
+mod reader;
+mod set;
+mod writer;
+
+pub use alpha::AlphaWorld;
+pub use check::{Anomaly, Issue};
+pub use compression::Compression;
+pub use entity::{Entity, PoiRecord, PoiSection};
+pub use reader::{ChunkInfo, Region};
+pub use set::RegionSet;
+
+/// The size of a sector in bytes.
+pub const SECTOR_SIZE: usize = 4096;
+
+/// The amount of chunks along each axis of a region.
+pub const REGION_WIDTH: i32 = 32;
+
+/// The flag added to the compression type of chunks stored in external
+/// files.
+const EXTERNAL_FLAG: u8 = 128;
+
+/// The amount of chunks in a region.
+const CHUNK_COUNT: usize = (REGION_WIDTH * REGION_WIDTH) as usize;
+
+/// Returns the index of a chunk in the location and timestamp tables.
+fn index(x: i32, z: i32) -> usize {
+    (x.rem_euclid(REGION_WIDTH) + z.rem_euclid(REGION_WIDTH) * REGION_WIDTH) as usize
+}
+
+/// Returns the coordinates and paths of all region files in the directory
+/// with the given extension ordered by their coordinates.
+///
+/// The extension is `mca` for Anvil and `mcr` for McRegion files.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mcnbt::region::{region_files, Region};
+///
+/// for (x, z, path) in region_files("world/region", "mcr").unwrap() {
+///     let mut region = Region::open(path).unwrap();
+///     println!("r.{}.{}.mcr: {} chunks", x, z, region.read_chunks().count());
+/// }
+/// ```
+pub fn region_files(
+    dir: impl AsRef<Path>,
+    extension: &str,
+) -> Result<Vec<(i32, i32, PathBuf)>, Error> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(extension) {
+            continue;
+        }
+        if let Some((x, z)) = region_coordinates(&path) {
+            files.push((x, z, path));
+        }
+    }
+    files.sort_unstable();
+    Ok(files)
+}
+
+/// Returns the coordinates of a region from its file name `r.<x>.<z>.mca`.
+fn region_coordinates(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("r"), Some(x), Some(z), Some(_)) if parts.next().is_none() => {
+            Some((x.parse().ok()?, z.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the path of the file `c.<x>.<z>.mcc` storing an oversized chunk
+/// of the region at the given path.
+fn external_path(region: &Path, x: i32, z: i32) -> Option<PathBuf> {
+    let (region_x, region_z) = region_coordinates(region)?;
+    let x = region_x * REGION_WIDTH + x.rem_euclid(REGION_WIDTH);
+    let z = region_z * REGION_WIDTH + z.rem_euclid(REGION_WIDTH);
+    Some(region.with_file_name(format!("c.{}.{}.mcc", x, z)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::byte_order::ByteOrder;
+    use crate::region::reader::test::region;
+    use crate::tag::Tag;
+    use crate::{byte, compound, int};
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            region_coordinates(Path::new("region/r.-1.2.mcr")),
+            Some((-1, 2))
+        );
+        assert_eq!(region_coordinates(Path::new("r.0.mca.bak")), None);
+        assert_eq!(
+            external_path(Path::new("region/r.-1.2.mca"), 33, -1),
+            Some(PathBuf::from("region/c.-31.95.mcc"))
+        );
+
+        let dir = std::env::temp_dir().join(format!("mcnbt-region-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "r.1.0.mcr",
+            "r.-1.0.mcr",
+            "r.0.0.mca",
+            "c.0.0.mcc",
+            "r.a.0.mcr",
+        ] {
+            fs::write(dir.join(name), []).unwrap();
+        }
+        let files = region_files(&dir, "mcr").unwrap();
+        assert_eq!(
+            files,
+            [
+                (-1, 0, dir.join("r.-1.0.mcr")),
+                (1, 0, dir.join("r.1.0.mcr"))
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mcregion() {
+        // chunks of McRegion files wrap their data in a `Level` compound
+        let chunk = |x: i32, z: i32| {
+            compound!("" => compound!("Level" =>
+                int!("xPos" => x),
+                int!("zPos" => z),
+                byte!("TerrainPopulated" => 1),
+                Tag::ByteArray(Some("Blocks".to_string()), vec![1; 32768]),
+            ))
+        };
+        let compressed = |x: i32, z: i32| {
+            let bytes = chunk(x, z).to_bytes(ByteOrder::BigEndian).unwrap();
+            (x, z, 2, Compression::Zlib.compress(&bytes).unwrap())
+        };
+
+        let dir = std::env::temp_dir().join(format!("mcnbt-mcregion-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("r.-1.0.mcr"),
+            region(&[compressed(-32, 0), compressed(-1, 31)]),
+        )
+        .unwrap();
+
+        let files = region_files(&dir, "mcr").unwrap();
+        assert_eq!(files, [(-1, 0, dir.join("r.-1.0.mcr"))]);
+        let mut region = Region::open(&files[0].2).unwrap();
+        let chunks: Vec<_> = region.read_chunks().map(Result::unwrap).collect();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].0.x, chunks[0].0.z), (0, 0));
+        assert_eq!(chunks[0].1, chunk(-32, 0));
+        assert_eq!(chunks[1].1["Level"]["zPos"].as_int(), Some(31));
+        assert_eq!(region.read_chunk(-1, 31).unwrap(), Some(chunk(-1, 31)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
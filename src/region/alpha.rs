@@ -0,0 +1,170 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::Compression;
+use crate::byte_order::ByteOrder;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Formats a number in base 36 like `Integer.toString(i, 36)` in Java.
+fn to_base36(i: i32) -> String {
+    let mut digits = vec![];
+    let mut n = i64::from(i).abs();
+    loop {
+        digits.push(std::char::from_digit((n % 36) as u32, 36).unwrap());
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    if i < 0 {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+/// Returns the coordinates of a chunk from its file name `c.<x>.<z>.dat`.
+fn chunk_coordinates(name: &str) -> Option<(i32, i32)> {
+    let mut parts = name.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("c"), Some(x), Some(z), Some("dat")) if parts.next().is_none() => Some((
+            i32::from_str_radix(x, 36).ok()?,
+            i32::from_str_radix(z, 36).ok()?,
+        )),
+        _ => None,
+    }
+}
+
+/// A world of Minecraft Alpha which stores every chunk in its own file.
+///
+/// The chunk at `x`, `z` is stored GZip compressed in
+/// `<x & 63>/<z & 63>/c.<x>.<z>.dat` where all numbers are in base 36.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mcnbt::region::AlphaWorld;
+///
+/// let world = AlphaWorld::new("saves/World1");
+/// for result in world.read_chunks() {
+///     let ((x, z), chunk) = result.unwrap();
+///     println!("{}, {}: {}", x, z, chunk["Level"]["LastUpdate"].pretty());
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlphaWorld {
+    dir: PathBuf,
+}
+
+impl AlphaWorld {
+    /// Creates a reader for the world in the given directory.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the path of the file storing the chunk.
+    pub fn chunk_path(&self, x: i32, z: i32) -> PathBuf {
+        self.dir
+            .join(to_base36(x & 63))
+            .join(to_base36(z & 63))
+            .join(format!("c.{}.{}.dat", to_base36(x), to_base36(z)))
+    }
+
+    /// Returns the coordinates of all chunks stored in the world ordered by
+    /// `x` and then by `z`.
+    pub fn chunks(&self) -> Result<Vec<(i32, i32)>, Error> {
+        let mut chunks = vec![];
+        for x_dir in fs::read_dir(&self.dir)? {
+            let x_dir = x_dir?;
+            if !x_dir.file_type()?.is_dir() {
+                continue;
+            }
+            for z_dir in fs::read_dir(x_dir.path())? {
+                let z_dir = z_dir?;
+                if !z_dir.file_type()?.is_dir() {
+                    continue;
+                }
+                for file in fs::read_dir(z_dir.path())? {
+                    let coordinates = file?.file_name().to_str().and_then(chunk_coordinates);
+                    chunks.extend(coordinates);
+                }
+            }
+        }
+        chunks.sort_unstable();
+        Ok(chunks)
+    }
+
+    /// Returns the chunk or [`None`] if it does not exist.
+    pub fn read_chunk(&self, x: i32, z: i32) -> Result<Option<Tag>, Error> {
+        let data = match fs::read(self.chunk_path(x, z)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let bytes = Compression::Gzip.decompress(&data)?;
+        Tag::from_bytes(&bytes, ByteOrder::BigEndian).map(Some)
+    }
+
+    /// Returns an iterator reading all chunks stored in the world ordered by
+    /// their coordinates.
+    ///
+    /// If the chunks cannot be listed, the iterator only yields that error.
+    pub fn read_chunks(&self) -> impl Iterator<Item = Result<((i32, i32), Tag), Error>> + '_ {
+        let chunks = match self.chunks() {
+            Ok(chunks) => chunks.into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        chunks.into_iter().filter_map(|chunk| match chunk {
+            Ok((x, z)) => self
+                .read_chunk(x, z)
+                .transpose()
+                .map(|chunk| chunk.map(|chunk| ((x, z), chunk))),
+            Err(e) => Some(Err(e)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::region::reader::test::chunk;
+
+    #[test]
+    fn base36() {
+        assert_eq!(to_base36(0), "0");
+        assert_eq!(to_base36(63), "1r");
+        assert_eq!(to_base36(-1), "-1");
+        assert_eq!(to_base36(-1295), "-zz");
+        assert_eq!(to_base36(i32::MIN), "-zik0zk");
+        assert_eq!(chunk_coordinates("c.-zz.1r.dat"), Some((-1295, 63)));
+        assert_eq!(chunk_coordinates("c.0.0.mcr"), None);
+    }
+
+    #[test]
+    fn read() {
+        let dir = std::env::temp_dir().join(format!("mcnbt-alpha-{}", std::process::id()));
+        let world = AlphaWorld::new(&dir);
+        assert_eq!(
+            world.chunk_path(-1, 40),
+            dir.join("1r").join("14").join("c.-1.14.dat")
+        );
+
+        for (x, z) in [(-1, 40), (2, 3)] {
+            let path = world.chunk_path(x, z);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let bytes = chunk(x, z).to_bytes(ByteOrder::BigEndian).unwrap();
+            fs::write(path, Compression::Gzip.compress(&bytes).unwrap()).unwrap();
+        }
+        fs::write(dir.join("level.dat"), []).unwrap();
+
+        assert_eq!(world.chunks().unwrap(), [(-1, 40), (2, 3)]);
+        assert_eq!(world.read_chunk(2, 3).unwrap(), Some(chunk(2, 3)));
+        assert_eq!(world.read_chunk(3, 3).unwrap(), None);
+        let chunks: Vec<_> = world.read_chunks().map(Result::unwrap).collect();
+        assert_eq!(chunks[0], ((-1, 40), chunk(-1, 40)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,387 @@
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::{index, Region, REGION_WIDTH, SECTOR_SIZE};
+use crate::byte_order::ByteOrder;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// A problem with a chunk stored in a region file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anomaly {
+    /// The location of the chunk has no sectors.
+    NoSectors,
+    /// The sectors of the chunk overlap the header.
+    OverlapsHeader,
+    /// The sectors of the chunk are shared with the chunk at the given
+    /// coordinates.
+    Overlaps { x: i32, z: i32 },
+    /// The file ends before the chunk.
+    Truncated,
+    /// The length of the chunk is zero.
+    ZeroLength,
+    /// The chunk is longer than its sectors.
+    TooLong,
+    /// The compression type of the chunk is unknown.
+    UnsupportedCompression(u8),
+    /// The external file of an oversized chunk cannot be found.
+    MissingExternal,
+    /// The chunk is stored in an external file which cannot be located
+    /// because the region was not opened from a file named `r.<x>.<z>.mca`.
+    UnknownExternal,
+    /// The chunk cannot be decompressed.
+    Corrupted(String),
+    /// The decompressed chunk is not valid NBT.
+    InvalidNbt(String),
+    /// The chunk stores the given absolute coordinates of a chunk at another
+    /// location.
+    WrongPosition { x: i32, z: i32 },
+}
+
+impl Anomaly {
+    /// Returns a description of the anomaly without its details.
+    fn reason(&self) -> &'static str {
+        match self {
+            Anomaly::NoSectors => "the chunk has no sectors",
+            Anomaly::OverlapsHeader => "the chunk overlaps the header",
+            Anomaly::Overlaps { .. } => "the chunk overlaps another chunk",
+            Anomaly::Truncated => "the chunk is truncated",
+            Anomaly::ZeroLength => "the chunk is empty",
+            Anomaly::TooLong => "the chunk is longer than its sectors",
+            Anomaly::UnsupportedCompression(_) => "the compression type is unsupported",
+            Anomaly::MissingExternal => "the external file of the chunk cannot be found",
+            Anomaly::UnknownExternal => "the chunk is stored in an unknown external file",
+            Anomaly::Corrupted(_) => "the chunk is corrupted",
+            Anomaly::InvalidNbt(_) => "the chunk is not valid NBT",
+            Anomaly::WrongPosition { .. } => {
+                "the coordinates of the chunk do not match its location"
+            }
+        }
+    }
+
+    /// Returns the error reading the chunk fails with.
+    pub(super) fn into_error(self, x: i32, z: i32) -> Error {
+        match self {
+            Anomaly::UnsupportedCompression(id) => Error::UnsupportedCompression(id),
+            anomaly => Error::InvalidChunk {
+                x,
+                z,
+                reason: anomaly.reason(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Anomaly::Overlaps { x, z } => write!(f, "the chunk overlaps chunk {}, {}", x, z),
+            Anomaly::WrongPosition { x, z } => {
+                write!(f, "the chunk stores the coordinates {}, {}", x, z)
+            }
+            Anomaly::UnsupportedCompression(id) => {
+                write!(f, "unsupported compression type {}", id)
+            }
+            Anomaly::Corrupted(details) | Anomaly::InvalidNbt(details) => {
+                write!(f, "{}: {}", self.reason(), details)
+            }
+            anomaly => f.write_str(anomaly.reason()),
+        }
+    }
+}
+
+/// Returns the absolute coordinates stored in a chunk of the `region`
+/// folder, which are wrapped in `Level` before Minecraft 1.18, or of the
+/// `entities` folder.
+fn chunk_position(chunk: &Tag) -> Option<(i32, i32)> {
+    if let Some([x, z]) = chunk.get("Position").and_then(Tag::as_int_array) {
+        return Some((*x, *z));
+    }
+    let level = chunk.get("Level").unwrap_or(chunk);
+    Some((level.get("xPos")?.as_int()?, level.get("zPos")?.as_int()?))
+}
+
+/// An anomaly of the chunk at the given coordinates within the region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+    /// The x coordinate of the chunk within the region.
+    pub x: i32,
+    /// The z coordinate of the chunk within the region.
+    pub z: i32,
+    /// What is wrong with the chunk.
+    pub anomaly: Anomaly,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chunk {}, {}: {}", self.x, self.z, self.anomaly)
+    }
+}
+
+impl<R: Read + Seek> Region<R> {
+    /// Validates every chunk of the region and returns all anomalies ordered
+    /// by the index of the chunks.
+    ///
+    /// Besides the anomalies which make reading a chunk fail, chunks sharing
+    /// sectors are reported. The chunk with the larger index is reported as
+    /// overlapping. Chunks whose `xPos` and `zPos` or `Position` do not
+    /// match their location are reported as well.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mcnbt::region::Region;
+    ///
+    /// let mut region = Region::open("world/region/r.0.0.mca").unwrap();
+    /// for issue in region.check().unwrap() {
+    ///     println!("{}", issue);
+    /// }
+    /// ```
+    pub fn check(&mut self) -> Result<Vec<Issue>, Error> {
+        let sectors = self
+            .inner
+            .seek(SeekFrom::End(0))?
+            .div_ceil(SECTOR_SIZE as u64);
+        let mut owners: Vec<Option<(i32, i32)>> = vec![None; sectors as usize];
+        let mut issues = vec![];
+
+        for info in self.chunks().collect::<Vec<_>>() {
+            let mut report = |anomaly| {
+                issues.push(Issue {
+                    x: info.x,
+                    z: info.z,
+                    anomaly,
+                })
+            };
+
+            let start = info.sector_offset.max(2) as usize;
+            let end = info.sector_offset as usize + usize::from(info.sector_count);
+            for owner in owners.iter_mut().take(end).skip(start) {
+                match owner {
+                    Some((x, z)) => {
+                        report(Anomaly::Overlaps { x: *x, z: *z });
+                        break;
+                    }
+                    None => *owner = Some((info.x, info.z)),
+                }
+            }
+
+            match self.read_payload(info)? {
+                Ok((compression, data)) => match compression.decompress(&data) {
+                    Ok(bytes) => match Tag::from_bytes(&bytes, ByteOrder::BigEndian) {
+                        Ok(chunk) => match chunk_position(&chunk) {
+                            Some((x, z))
+                                if (x.rem_euclid(REGION_WIDTH), z.rem_euclid(REGION_WIDTH))
+                                    != (info.x, info.z) =>
+                            {
+                                report(Anomaly::WrongPosition { x, z })
+                            }
+                            _ => {}
+                        },
+                        Err(e) => report(Anomaly::InvalidNbt(e.to_string())),
+                    },
+                    Err(e) => report(Anomaly::Corrupted(e.to_string())),
+                },
+                Err(anomaly) => report(anomaly),
+            }
+        }
+        Ok(issues)
+    }
+}
+
+impl<R: Read + Write + Seek> Region<R> {
+    /// Repairs the region and returns the anomalies found before.
+    ///
+    /// Broken chunks and chunks storing the coordinates of another chunk are
+    /// removed. Chunks stored in an unknown external file are kept as they
+    /// may be intact. Thus, of chunks sharing sectors, only the one owning the data
+    /// is kept. Chunks whose only anomaly is sharing sectors with another
+    /// chunk which is kept as well are moved to free sectors. Afterwards, the
+    /// whole header is rewritten.
+    pub fn repair(&mut self) -> Result<Vec<Issue>, Error> {
+        let issues = self.check()?;
+
+        let mut relocate = vec![];
+        for issue in &issues {
+            if let Anomaly::Overlaps { x, z } = issue.anomaly {
+                relocate.push((issue.x, issue.z, x, z));
+            } else if issue.anomaly != Anomaly::UnknownExternal {
+                let i = index(issue.x, issue.z);
+                self.locations[i] = 0;
+                self.timestamps[i] = 0;
+            }
+        }
+
+        for (x, z, other_x, other_z) in relocate {
+            // the sectors belong to the chunk alone if the other one was removed
+            if self.chunk_info(other_x, other_z).is_none() {
+                continue;
+            }
+            let Some(info) = self.chunk_info(x, z) else {
+                continue;
+            };
+            if let Ok((compression, data)) = self.read_payload(info)? {
+                self.store(x, z, compression, &data, info.timestamp)?;
+            }
+        }
+
+        self.write_header()?;
+        Ok(issues)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compound;
+    use crate::region::reader::test::{chunk, region};
+    use crate::region::{Compression, EXTERNAL_FLAG};
+    use std::io::Cursor;
+
+    #[test]
+    fn check() {
+        let bytes = |x, z| chunk(x, z).to_bytes(ByteOrder::BigEndian).unwrap();
+        let mut file = region(&[
+            (0, 0, 3, bytes(0, 0)),
+            (1, 0, 3, vec![]),
+            (2, 0, 9, bytes(2, 0)),
+            (3, 0, 2, bytes(3, 0)),
+            (4, 0, 3, vec![1, 2, 3]),
+            (5, 0, 3, bytes(5, 0)),
+        ]);
+        // the length of chunk 1, 0 is zero
+        file[3 * SECTOR_SIZE..3 * SECTOR_SIZE + 4].copy_from_slice(&[0; 4]);
+        // chunk 6, 0 shares the sector of chunk 0, 0
+        file[24..28].copy_from_slice(&(2 << 8 | 1_u32).to_be_bytes());
+        // chunk 7, 0 starts within the header
+        file[28..32].copy_from_slice(&(1 << 8 | 1_u32).to_be_bytes());
+        // chunk 8, 0 ends after the file
+        file[32..36].copy_from_slice(&(8 << 8 | 2_u32).to_be_bytes());
+        // the last chunk is truncated
+        file.truncate(file.len() - SECTOR_SIZE + 10);
+
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+        let issues: Vec<_> = region
+            .check()
+            .unwrap()
+            .into_iter()
+            .map(|issue| (issue.x, issue.anomaly))
+            .collect();
+        assert!(matches!(
+            &issues[..],
+            [
+                (1, Anomaly::ZeroLength),
+                (2, Anomaly::UnsupportedCompression(9)),
+                (3, Anomaly::Corrupted(_)),
+                (4, Anomaly::InvalidNbt(_)),
+                (5, Anomaly::Truncated),
+                (6, Anomaly::Overlaps { x: 0, z: 0 }),
+                (6, Anomaly::WrongPosition { x: 0, z: 0 }),
+                (7, Anomaly::OverlapsHeader),
+                (8, Anomaly::Truncated),
+            ]
+        ));
+        assert_eq!(
+            Issue {
+                x: 2,
+                z: 0,
+                anomaly: Anomaly::UnsupportedCompression(9)
+            }
+            .to_string(),
+            "chunk 2, 0: unsupported compression type 9"
+        );
+        assert_eq!(Anomaly::ZeroLength.to_string(), "the chunk is empty");
+    }
+
+    #[test]
+    fn repair() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        for x in 0..3 {
+            region.write_chunk(x, 0, &chunk(x, 0)).unwrap();
+        }
+        region
+            .write_raw_chunk(3, 0, Compression::None, &[1, 2, 3])
+            .unwrap();
+        region.write_chunk(5, 0, &chunk(-27, 32)).unwrap();
+        region.write_chunk(6, 0, &chunk(6, 0)).unwrap();
+
+        let mut file = region.into_inner().into_inner();
+        // chunk 4, 0 shares the sector of chunk 1, 0 which owns the data
+        file.copy_within(4..8, 16);
+        // chunk 0, 0 shares the sector of chunk 6, 0 which owns the data
+        file.copy_within(24..28, 0);
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+
+        let issues: Vec<_> = region
+            .repair()
+            .unwrap()
+            .into_iter()
+            .map(|issue| (issue.x, issue.anomaly))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (0, Anomaly::WrongPosition { x: 6, z: 0 }),
+                (3, Anomaly::InvalidNbt("failed to parse NBT".to_string())),
+                (4, Anomaly::Overlaps { x: 1, z: 0 }),
+                (4, Anomaly::WrongPosition { x: 1, z: 0 }),
+                (6, Anomaly::Overlaps { x: 0, z: 0 }),
+            ]
+        );
+        assert!(region.check().unwrap().is_empty());
+        assert_eq!(region.chunk_info(0, 0), None);
+        assert_eq!(region.chunk_info(3, 0), None);
+        assert_eq!(region.chunk_info(4, 0), None);
+        // the owner of the sectors of a removed chunk is not moved
+        assert_eq!(region.chunk_info(6, 0).unwrap().sector_offset, 7);
+
+        let mut region = Region::new(Cursor::new(region.into_inner().into_inner())).unwrap();
+        assert_eq!(region.read_chunk(1, 0).unwrap(), Some(chunk(1, 0)));
+        assert_eq!(region.read_chunk(5, 0).unwrap(), Some(chunk(-27, 32)));
+        assert_eq!(region.read_chunk(6, 0).unwrap(), Some(chunk(6, 0)));
+        assert_eq!(region.chunks().count(), 4);
+    }
+
+    #[test]
+    fn unknown_external() {
+        let file = region(&[(0, 0, Compression::Zlib.id() | EXTERNAL_FLAG, vec![])]);
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+        let issues = region.repair().unwrap();
+        assert_eq!(
+            issues,
+            [Issue {
+                x: 0,
+                z: 0,
+                anomaly: Anomaly::UnknownExternal
+            }]
+        );
+        assert!(region.chunk_info(0, 0).is_some());
+        assert!(matches!(
+            region.read_chunk(0, 0),
+            Err(Error::InvalidChunk { x: 0, z: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn relocate() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        let poi = compound!("" => compound!("Sections" =>));
+        for x in 0..2 {
+            region.write_chunk(x, 0, &poi).unwrap();
+        }
+        let timestamp = region.chunk_info(1, 0).unwrap().timestamp;
+
+        // chunks without coordinates cannot tell which one owns the data
+        let mut file = region.into_inner().into_inner();
+        file.copy_within(0..4, 4);
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(region.repair().unwrap().len(), 1);
+        assert!(region.check().unwrap().is_empty());
+        // the shared sector stays with chunk 0, 0
+        assert_eq!(region.chunk_info(0, 0).unwrap().sector_offset, 2);
+        assert_eq!(region.chunk_info(1, 0).unwrap().sector_offset, 3);
+        assert_eq!(region.chunk_info(1, 0).unwrap().timestamp, timestamp);
+        assert_eq!(region.read_chunk(1, 0).unwrap(), Some(poi));
+    }
+}
@@ -0,0 +1,314 @@
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use std::io::{Read, Write};
+
+use crate::errors::Error;
+
+/// The magic bytes starting every block written by `LZ4BlockOutputStream`.
+const LZ4_MAGIC: &[u8] = b"LZ4Block";
+/// The length of the header of such a block.
+const LZ4_HEADER_LEN: usize = LZ4_MAGIC.len() + 13;
+const LZ4_METHOD_RAW: u8 = 0x10;
+const LZ4_METHOD_LZ4: u8 = 0x20;
+/// The size of the blocks written by `LZ4BlockOutputStream` by default.
+const LZ4_BLOCK_SIZE: usize = 1 << 16;
+/// The lower half of the token, which encodes the block size.
+const LZ4_BLOCK_SIZE_LEVEL: u8 = 6;
+const LZ4_CHECKSUM_SEED: u32 = 0x9747b28c;
+/// lz4-java only keeps the lower 28 bits of the checksums.
+const LZ4_CHECKSUM_MASK: u32 = 0x0fff_ffff;
+
+/// The compression of a chunk stored in a region file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// GZip (RFC1952), which the game never writes but can read.
+    Gzip,
+    /// Zlib (RFC1950), the default of the game.
+    Zlib,
+    /// No compression at all.
+    None,
+    /// LZ4 in the block stream format of lz4-java, available since Minecraft
+    /// 1.20.5.
+    Lz4,
+}
+
+impl Compression {
+    /// Returns the compression type stored in the chunk header.
+    pub fn id(self) -> u8 {
+        match self {
+            Compression::Gzip => 1,
+            Compression::Zlib => 2,
+            Compression::None => 3,
+            Compression::Lz4 => 4,
+        }
+    }
+
+    /// Returns the compression for a compression type stored in the chunk
+    /// header.
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Compression::Gzip),
+            2 => Ok(Compression::Zlib),
+            3 => Ok(Compression::None),
+            4 => Ok(Compression::Lz4),
+            id => Err(Error::UnsupportedCompression(id)),
+        }
+    }
+
+    /// Decompresses the payload of a chunk.
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![];
+        match self {
+            Compression::Gzip => {
+                GzDecoder::new(data).read_to_end(&mut buf)?;
+            }
+            Compression::Zlib => {
+                ZlibDecoder::new(data).read_to_end(&mut buf)?;
+            }
+            Compression::None => buf.extend_from_slice(data),
+            Compression::Lz4 => decompress_lz4(data, &mut buf)?,
+        }
+        Ok(buf)
+    }
+
+    /// Compresses the payload of a chunk.
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::Zlib => {
+                let mut encoder = ZlibEncoder::new(vec![], flate2::Compression::default());
+                encoder.write_all(data)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::None => Ok(data.to_vec()),
+            Compression::Lz4 => Ok(compress_lz4(data)),
+        }
+    }
+}
+
+/// Computes the 32-bit xxHash of the data.
+fn xxh32(data: &[u8], seed: u32) -> u32 {
+    const PRIME_1: u32 = 0x9e3779b1;
+    const PRIME_2: u32 = 0x85ebca77;
+    const PRIME_3: u32 = 0xc2b2ae3d;
+    const PRIME_4: u32 = 0x27d4eb2f;
+    const PRIME_5: u32 = 0x165667b1;
+
+    let word = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+    let round = |acc: u32, lane: u32| {
+        acc.wrapping_add(lane.wrapping_mul(PRIME_2))
+            .rotate_left(13)
+            .wrapping_mul(PRIME_1)
+    };
+
+    let mut stripes = data.chunks_exact(16);
+    let mut hash = if data.len() >= 16 {
+        let mut acc = [
+            seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2),
+            seed.wrapping_add(PRIME_2),
+            seed,
+            seed.wrapping_sub(PRIME_1),
+        ];
+        for stripe in &mut stripes {
+            for (i, acc) in acc.iter_mut().enumerate() {
+                *acc = round(*acc, word(&stripe[i * 4..i * 4 + 4]));
+            }
+        }
+        acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18))
+    } else {
+        seed.wrapping_add(PRIME_5)
+    };
+    hash = hash.wrapping_add(data.len() as u32);
+
+    let mut words = stripes.remainder().chunks_exact(4);
+    for bytes in &mut words {
+        hash = hash
+            .wrapping_add(word(bytes).wrapping_mul(PRIME_3))
+            .rotate_left(17)
+            .wrapping_mul(PRIME_4);
+    }
+    for byte in words.remainder() {
+        hash = hash
+            .wrapping_add(u32::from(*byte).wrapping_mul(PRIME_5))
+            .rotate_left(11)
+            .wrapping_mul(PRIME_1);
+    }
+
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(PRIME_2);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(PRIME_3);
+    hash ^ (hash >> 16)
+}
+
+/// Returns the checksum of a block as written by `LZ4BlockOutputStream`.
+fn lz4_checksum(block: &[u8]) -> u32 {
+    xxh32(block, LZ4_CHECKSUM_SEED) & LZ4_CHECKSUM_MASK
+}
+
+/// Compresses data like `LZ4BlockOutputStream` does.
+///
+/// Blocks which do not become smaller are stored uncompressed.
+fn compress_lz4(data: &[u8]) -> Vec<u8> {
+    let mut buf = vec![];
+    let end: &[u8] = &[];
+    for block in data.chunks(LZ4_BLOCK_SIZE).chain([end]) {
+        let compressed = lz4_flex::block::compress(block);
+        let (method, payload) = if !block.is_empty() && compressed.len() < block.len() {
+            (LZ4_METHOD_LZ4, &compressed[..])
+        } else {
+            (LZ4_METHOD_RAW, block)
+        };
+        let checksum = if block.is_empty() {
+            0
+        } else {
+            lz4_checksum(block)
+        };
+
+        buf.extend_from_slice(LZ4_MAGIC);
+        buf.push(method | LZ4_BLOCK_SIZE_LEVEL);
+        buf.extend((payload.len() as i32).to_le_bytes());
+        buf.extend((block.len() as i32).to_le_bytes());
+        buf.extend(checksum.to_le_bytes());
+        buf.extend_from_slice(payload);
+    }
+    buf
+}
+
+fn invalid_lz4(reason: &str) -> Error {
+    Error::InvalidRegion(format!("invalid LZ4 block: {}", reason))
+}
+
+/// Decompresses a sequence of blocks written by `LZ4BlockOutputStream`.
+///
+/// Every block consists of the magic bytes, a token whose upper half is the
+/// compression method, the compressed and decompressed length, a checksum
+/// and the data. The stream ends with an empty block.
+fn decompress_lz4(mut data: &[u8], buf: &mut Vec<u8>) -> Result<(), Error> {
+    while !data.is_empty() {
+        if data.len() < LZ4_HEADER_LEN || !data.starts_with(LZ4_MAGIC) {
+            return Err(invalid_lz4("missing header"));
+        }
+        let header = &data[LZ4_MAGIC.len()..LZ4_HEADER_LEN];
+        let method = header[0] & 0xf0;
+        let int = |i: usize| {
+            i32::from_le_bytes(header[i..i + 4].try_into().unwrap())
+                .try_into()
+                .map_err(|_| invalid_lz4("negative length"))
+        };
+        let compressed_len: usize = int(1)?;
+        let decompressed_len: usize = int(5)?;
+        let checksum = u32::from_le_bytes(header[9..13].try_into().unwrap());
+
+        data = &data[LZ4_HEADER_LEN..];
+        if data.len() < compressed_len {
+            return Err(invalid_lz4("truncated data"));
+        }
+        let (block, rest) = data.split_at(compressed_len);
+        data = rest;
+
+        if decompressed_len == 0 {
+            break;
+        }
+        // the token stores the block size to reject lengths of corrupt blocks
+        // before allocating
+        if decompressed_len > 1 << (10 + (header[0] & 0x0f)) {
+            return Err(invalid_lz4(
+                "the decompressed length exceeds the block size",
+            ));
+        }
+        let start = buf.len();
+        match method {
+            LZ4_METHOD_RAW if decompressed_len != compressed_len => {
+                return Err(invalid_lz4("wrong decompressed length"));
+            }
+            LZ4_METHOD_RAW => buf.extend_from_slice(block),
+            LZ4_METHOD_LZ4 => {
+                buf.resize(start + decompressed_len, 0);
+                let written = lz4_flex::block::decompress_into(block, &mut buf[start..])
+                    .map_err(|_| invalid_lz4("corrupted data"))?;
+                if written != decompressed_len {
+                    return Err(invalid_lz4("wrong decompressed length"));
+                }
+            }
+            _ => return Err(invalid_lz4("unknown compression method")),
+        }
+        if lz4_checksum(&buf[start..]) != checksum {
+            return Err(invalid_lz4("wrong checksum"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data = b"hello hello hello hello world".repeat(10000);
+        for compression in [
+            Compression::Gzip,
+            Compression::Zlib,
+            Compression::None,
+            Compression::Lz4,
+        ] {
+            let compressed = compression.compress(&data).unwrap();
+            assert_eq!(compression.decompress(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn lz4() {
+        let data = b"hello hello hello hello world".repeat(10);
+        let mut compressed = Compression::Lz4.compress(&data).unwrap();
+        assert_eq!(compressed[LZ4_MAGIC.len()], 0x26);
+        assert_eq!(Compression::Lz4.decompress(&compressed).unwrap(), data);
+
+        // the checksum of the first block
+        compressed[LZ4_HEADER_LEN - 1] ^= 1;
+        assert!(Compression::Lz4.decompress(&compressed).is_err());
+        assert!(Compression::Lz4.decompress(b"LZ4Block").is_err());
+
+        // a corrupt decompressed length must not be allocated
+        let mut compressed = Compression::Lz4.compress(&data).unwrap();
+        let len = LZ4_MAGIC.len() + 5;
+        compressed[len..len + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+        assert!(Compression::Lz4.decompress(&compressed).is_err());
+
+        // a raw block whose lengths differ
+        let mut raw = LZ4_MAGIC.to_vec();
+        raw.push(LZ4_METHOD_RAW | 6);
+        raw.extend(3_i32.to_le_bytes());
+        raw.extend(2_i32.to_le_bytes());
+        raw.extend(lz4_checksum(b"abc").to_le_bytes());
+        raw.extend(b"abc");
+        assert!(Compression::Lz4.decompress(&raw).is_err());
+    }
+
+    #[test]
+    fn xxhash() {
+        assert_eq!(xxh32(b"", 0), 0x02cc5d05);
+        assert_eq!(xxh32(b"abc", 0), 0x32d153ff);
+        assert_eq!(
+            xxh32(b"Nobody inspects the spammish repetition", 0),
+            0xe2293b2f
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(matches!(
+            Compression::from_id(127),
+            Err(Error::UnsupportedCompression(127))
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::convert::FromTag;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Reads the child of a compound with the given name.
+fn field<T: FromTag>(tag: &Tag, name: &str) -> Result<T, Error> {
+    tag.get(name)
+        .ok_or_else(|| Error::MissingTag(name.to_string()))
+        .and_then(T::from_tag)
+}
+
+/// Reads the int array of a compound with the given name and length.
+fn int_array<const N: usize>(
+    tag: &Tag,
+    name: &str,
+    expected: &'static str,
+) -> Result<[i32; N], Error> {
+    let array = tag
+        .get(name)
+        .ok_or_else(|| Error::MissingTag(name.to_string()))?;
+    array
+        .as_int_array()
+        .and_then(|ints| ints.try_into().ok())
+        .ok_or(Error::UnexpectedType {
+            expected,
+            found: array.name(),
+        })
+}
+
+/// An entity stored in a chunk of the `entities` folder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entity {
+    /// The type of the entity such as `minecraft:pig`.
+    pub id: String,
+    /// The position of the entity.
+    pub pos: [f64; 3],
+    /// The UUID of the entity.
+    pub uuid: u128,
+    /// All data of the entity.
+    pub tag: Tag,
+}
+
+impl Entity {
+    /// Reads the entities of a chunk of the `entities` folder.
+    ///
+    /// Passengers are not included but are part of the tag of the entity
+    /// they ride.
+    pub fn from_chunk(chunk: &Tag) -> Result<Vec<Self>, Error> {
+        field(chunk, "Entities")
+    }
+}
+
+impl FromTag for Entity {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        let pos: Vec<f64> = field(tag, "Pos")?;
+        let pos = pos.try_into().map_err(|_| Error::UnexpectedType {
+            expected: "TAG_List of 3 TAG_Double",
+            found: "TAG_List",
+        })?;
+        let uuid = int_array::<4>(tag, "UUID", "TAG_Int_Array of length 4")?
+            .iter()
+            .fold(0, |uuid, int| uuid << 32 | u128::from(*int as u32));
+        Ok(Self {
+            id: field(tag, "id")?,
+            pos,
+            uuid,
+            tag: Tag::from_tag(tag)?,
+        })
+    }
+}
+
+/// A point of interest such as a bed or a workstation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoiRecord {
+    /// The position of the block.
+    pub pos: [i32; 3],
+    /// The type of the point of interest such as `minecraft:home`.
+    pub kind: String,
+    /// How many more villagers can claim the point of interest.
+    pub free_tickets: i32,
+}
+
+impl FromTag for PoiRecord {
+    fn from_tag(tag: &Tag) -> Result<Self, Error> {
+        Ok(Self {
+            pos: int_array(tag, "pos", "TAG_Int_Array of length 3")?,
+            kind: field(tag, "type")?,
+            free_tickets: field(tag, "free_tickets")?,
+        })
+    }
+}
+
+/// The points of interest within a section of a chunk of the `poi` folder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoiSection {
+    /// The y coordinate of the section.
+    pub y: i32,
+    /// Whether the game considers the records to be up to date.
+    pub valid: bool,
+    /// The points of interest within the section.
+    pub records: Vec<PoiRecord>,
+}
+
+impl PoiSection {
+    /// Reads the sections of a chunk of the `poi` folder ordered by their y
+    /// coordinate.
+    pub fn from_chunk(chunk: &Tag) -> Result<Vec<Self>, Error> {
+        let sections = chunk
+            .get("Sections")
+            .ok_or_else(|| Error::MissingTag("Sections".to_string()))?;
+        let mut sections = sections
+            .as_compound()
+            .ok_or(Error::UnexpectedType {
+                expected: "TAG_Compound",
+                found: sections.name(),
+            })?
+            .iter()
+            .map(|section| {
+                let name = section.tag_name().unwrap_or_default();
+                Ok(Self {
+                    y: name
+                        .parse()
+                        .map_err(|_| Error::UnknownTag(name.to_string()))?,
+                    valid: field(section, "Valid")?,
+                    records: field(section, "Records")?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        sections.sort_by_key(|section| section.y);
+        Ok(sections)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{byte, compound, double, int, list, string};
+
+    pub(crate) fn entities(x: i32, z: i32) -> Tag {
+        compound!("" =>
+            int!("DataVersion" => 3465),
+            Tag::IntArray(Some("Position".to_string()), vec![x, z]),
+            list!("Entities" => compound!(
+                string!("id" => "minecraft:pig"),
+                list!("Pos" => double!(x as f64 * 16.0 + 0.5), double!(64.0), double!(z as f64 * 16.0)),
+                Tag::IntArray(Some("UUID".to_string()), vec![1, -1, 0, 2]),
+                list!("Passengers" => compound!(string!("id" => "minecraft:zombie"))),
+            )),
+        )
+    }
+
+    pub(crate) fn poi() -> Tag {
+        compound!("" =>
+            int!("DataVersion" => 3465),
+            compound!("Sections" =>
+                compound!("4" => byte!("Valid" => 1), list!("Records" =>
+                    compound!(
+                        Tag::IntArray(Some("pos".to_string()), vec![1, 70, 2]),
+                        string!("type" => "minecraft:home"),
+                        int!("free_tickets" => 1),
+                    ),
+                )),
+                compound!("-1" => byte!("Valid" => 0), list!("Records" =>)),
+            ),
+        )
+    }
+
+    #[test]
+    fn entity() {
+        let entities = Entity::from_chunk(&entities(2, -1)).unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(entities[0].id, "minecraft:pig");
+        assert_eq!(entities[0].pos, [32.5, 64.0, -16.0]);
+        assert_eq!(entities[0].uuid, 0x00000001_ffffffff_00000000_00000002);
+        assert_eq!(entities[0].tag["Passengers"].as_list().unwrap().len(), 1);
+
+        let invalid = compound!(list!("Entities" => compound!(string!("id" => "minecraft:pig"))));
+        assert!(matches!(
+            Entity::from_chunk(&invalid),
+            Err(Error::MissingTag(name)) if name == "Pos"
+        ));
+    }
+
+    #[test]
+    fn poi_sections() {
+        let sections = PoiSection::from_chunk(&poi()).unwrap();
+        assert_eq!(
+            sections,
+            [
+                PoiSection {
+                    y: -1,
+                    valid: false,
+                    records: vec![],
+                },
+                PoiSection {
+                    y: 4,
+                    valid: true,
+                    records: vec![PoiRecord {
+                        pos: [1, 70, 2],
+                        kind: "minecraft:home".to_string(),
+                        free_tickets: 1,
+                    }],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::check::Anomaly;
+use super::{
+    external_path, index, Compression, CHUNK_COUNT, EXTERNAL_FLAG, REGION_WIDTH, SECTOR_SIZE,
+};
+use crate::byte_order::ByteOrder;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Where a chunk is stored in a region file and when it was saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// The x coordinate of the chunk within the region.
+    pub x: i32,
+    /// The z coordinate of the chunk within the region.
+    pub z: i32,
+    /// The time the chunk was saved in seconds since the Unix epoch.
+    pub timestamp: u32,
+    /// The offset of the chunk in sectors.
+    pub sector_offset: u32,
+    /// The amount of sectors occupied by the chunk.
+    pub sector_count: u8,
+}
+
+/// A region file.
+///
+/// Chunks can be read from any region and written to regions whose
+/// underlying file or buffer is writable.
+#[derive(Debug)]
+pub struct Region<R> {
+    pub(super) inner: R,
+    /// The path of the region file, which is needed to locate the files of
+    /// oversized chunks.
+    pub(super) path: Option<PathBuf>,
+    pub(super) locations: Vec<u32>,
+    pub(super) timestamps: Vec<u32>,
+}
+
+impl Region<File> {
+    /// Opens the region file at the given path for reading.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut region = Self::new(File::open(path)?)?;
+        region.path = Some(path.to_path_buf());
+        Ok(region)
+    }
+}
+
+/// Reads a table of big endian integers. The table is all zeros if the
+/// reader is at its end.
+fn read_table(reader: &mut impl Read) -> Result<Vec<u32>, Error> {
+    let mut buf = vec![0; SECTOR_SIZE];
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    if read != 0 && read != buf.len() {
+        return Err(Error::InvalidRegion("the header is truncated".to_string()));
+    }
+    Ok(buf
+        .chunks_exact(4)
+        .map(|int| u32::from_be_bytes(int.try_into().unwrap()))
+        .collect())
+}
+
+/// Fills the buffer and returns `false` if the reader ends before.
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<bool, Error> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl<R: Read + Seek> Region<R> {
+    /// Reads the header of a region file.
+    ///
+    /// An empty file is an empty region.
+    pub fn new(mut inner: R) -> Result<Self, Error> {
+        inner.seek(SeekFrom::Start(0))?;
+        let locations = read_table(&mut inner)?;
+        let timestamps = read_table(&mut inner)?;
+        Ok(Self {
+            inner,
+            path: None,
+            locations,
+            timestamps,
+        })
+    }
+
+    /// Returns where the chunk is stored or [`None`] if it does not exist.
+    pub fn chunk_info(&self, x: i32, z: i32) -> Option<ChunkInfo> {
+        let i = index(x, z);
+        let location = self.locations[i];
+        if location == 0 {
+            return None;
+        }
+        Some(ChunkInfo {
+            x: i as i32 % REGION_WIDTH,
+            z: i as i32 / REGION_WIDTH,
+            timestamp: self.timestamps[i],
+            sector_offset: location >> 8,
+            sector_count: location as u8,
+        })
+    }
+
+    /// Returns all chunks stored in the region ordered by their index.
+    pub fn chunks(&self) -> impl Iterator<Item = ChunkInfo> + '_ {
+        (0..CHUNK_COUNT as i32).filter_map(|i| self.chunk_info(i % REGION_WIDTH, i / REGION_WIDTH))
+    }
+
+    /// Returns the compression and the compressed NBT of the chunk.
+    ///
+    /// The NBT of oversized chunks is read from their `c.<x>.<z>.mcc` file,
+    /// which requires the region to be opened from a path.
+    pub fn read_raw_chunk(
+        &mut self,
+        x: i32,
+        z: i32,
+    ) -> Result<Option<(Compression, Vec<u8>)>, Error> {
+        let Some(info) = self.chunk_info(x, z) else {
+            return Ok(None);
+        };
+        match self.read_payload(info)? {
+            Ok(payload) => Ok(Some(payload)),
+            Err(anomaly) => Err(anomaly.into_error(info.x, info.z)),
+        }
+    }
+
+    /// Returns the compression and the compressed NBT of an existing chunk
+    /// or why they cannot be read.
+    pub(super) fn read_payload(
+        &mut self,
+        info: ChunkInfo,
+    ) -> Result<Result<(Compression, Vec<u8>), Anomaly>, Error> {
+        if info.sector_count == 0 {
+            return Ok(Err(Anomaly::NoSectors));
+        }
+        if info.sector_offset < 2 {
+            return Ok(Err(Anomaly::OverlapsHeader));
+        }
+
+        self.inner.seek(SeekFrom::Start(
+            u64::from(info.sector_offset) * SECTOR_SIZE as u64,
+        ))?;
+        let mut header = [0; 5];
+        if !read_exact(&mut self.inner, &mut header)? {
+            return Ok(Err(Anomaly::Truncated));
+        }
+        let len = u32::from_be_bytes(header[..4].try_into().unwrap());
+        if len == 0 {
+            return Ok(Err(Anomaly::ZeroLength));
+        }
+        // compared as u64 to not overflow on 32-bit targets
+        if u64::from(len) + 4 > u64::from(info.sector_count) * SECTOR_SIZE as u64 {
+            return Ok(Err(Anomaly::TooLong));
+        }
+        let len = len as usize;
+        let Ok(compression) = Compression::from_id(header[4] & !EXTERNAL_FLAG) else {
+            return Ok(Err(Anomaly::UnsupportedCompression(header[4])));
+        };
+
+        if header[4] & EXTERNAL_FLAG != 0 {
+            let Some(path) = self
+                .path
+                .as_deref()
+                .and_then(|path| external_path(path, info.x, info.z))
+            else {
+                return Ok(Err(Anomaly::UnknownExternal));
+            };
+            return match fs::read(path) {
+                Ok(data) => Ok(Ok((compression, data))),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Err(Anomaly::MissingExternal)),
+                Err(e) => Err(e.into()),
+            };
+        }
+
+        let mut data = vec![0; len - 1];
+        if !read_exact(&mut self.inner, &mut data)? {
+            return Ok(Err(Anomaly::Truncated));
+        }
+        Ok(Ok((compression, data)))
+    }
+
+    /// Returns the uncompressed NBT of the chunk.
+    pub fn read_chunk_bytes(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>, Error> {
+        match self.read_raw_chunk(x, z)? {
+            Some((compression, data)) => compression.decompress(&data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the chunk or [`None`] if it does not exist.
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Tag>, Error> {
+        match self.read_chunk_bytes(x, z)? {
+            Some(bytes) => Tag::from_bytes(&bytes, ByteOrder::BigEndian).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an iterator reading all chunks stored in the region ordered
+    /// by their index.
+    pub fn read_chunks(&mut self) -> impl Iterator<Item = Result<(ChunkInfo, Tag), Error>> + '_ {
+        let chunks: Vec<_> = self.chunks().collect();
+        chunks.into_iter().filter_map(|info| {
+            self.read_chunk(info.x, info.z)
+                .transpose()
+                .map(|chunk| chunk.map(|chunk| (info, chunk)))
+        })
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading or writing through it may leave the region in an
+    /// inconsistent state.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{compound, int, string};
+    use std::io::Cursor;
+
+    /// Builds a region file with one chunk per sector.
+    pub(crate) fn region(chunks: &[(i32, i32, u8, Vec<u8>)]) -> Vec<u8> {
+        let mut file = vec![0; 2 * SECTOR_SIZE];
+        for (n, (x, z, compression, data)) in chunks.iter().enumerate() {
+            let i = index(*x, *z);
+            let offset = 2 + n as u32;
+            file[i * 4..i * 4 + 4].copy_from_slice(&(offset << 8 | 1).to_be_bytes());
+            file[SECTOR_SIZE + i * 4..SECTOR_SIZE + i * 4 + 4]
+                .copy_from_slice(&(1000 + n as u32).to_be_bytes());
+
+            let mut sector = vec![];
+            sector.extend((data.len() as u32 + 1).to_be_bytes());
+            sector.push(*compression);
+            sector.extend(data);
+            sector.resize(SECTOR_SIZE, 0);
+            file.extend(sector);
+        }
+        file
+    }
+
+    pub(crate) fn chunk(x: i32, z: i32) -> Tag {
+        compound!("" => int!("xPos" => x), int!("zPos" => z), string!("Status" => "full"))
+    }
+
+    #[test]
+    fn compressions() {
+        let bytes = |x, z| chunk(x, z).to_bytes(ByteOrder::BigEndian).unwrap();
+
+        let compressed = |x, z, compression: Compression| {
+            (
+                x,
+                z,
+                compression.id(),
+                compression.compress(&bytes(x, z)).unwrap(),
+            )
+        };
+
+        let file = region(&[
+            compressed(0, 0, Compression::Gzip),
+            compressed(31, 0, Compression::Zlib),
+            compressed(0, 31, Compression::None),
+            compressed(5, 7, Compression::Lz4),
+        ]);
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+
+        let chunks: Vec<_> = region.chunks().map(|info| (info.x, info.z)).collect();
+        assert_eq!(chunks, [(0, 0), (31, 0), (5, 7), (0, 31)]);
+        assert_eq!(region.chunk_info(-1, 0).unwrap().timestamp, 1001);
+        for (x, z) in chunks {
+            assert_eq!(region.read_chunk(x, z).unwrap(), Some(chunk(x, z)));
+        }
+        assert_eq!(region.read_chunk(1, 1).unwrap(), None);
+
+        let chunks: Vec<_> = region
+            .read_chunks()
+            .map(|result| result.unwrap().1)
+            .collect();
+        assert_eq!(chunks[3], chunk(0, 31));
+    }
+
+    #[test]
+    fn empty() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        assert_eq!(region.chunks().count(), 0);
+        assert_eq!(region.read_chunk(0, 0).unwrap(), None);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Region::new(Cursor::new(vec![0; 100])).is_err());
+
+        let mut truncated = region(&[(0, 0, 3, vec![0; 10])]);
+        truncated.truncate(2 * SECTOR_SIZE + 8);
+        let unsupported = region(&[(0, 0, 127, vec![])]);
+
+        let mut truncated = Region::new(Cursor::new(truncated)).unwrap();
+        assert!(matches!(
+            truncated.read_chunk(0, 0),
+            Err(Error::InvalidChunk { x: 0, z: 0, .. })
+        ));
+        let mut unsupported = Region::new(Cursor::new(unsupported)).unwrap();
+        assert!(matches!(
+            unsupported.read_chunk(0, 0),
+            Err(Error::UnsupportedCompression(127))
+        ));
+    }
+
+    #[test]
+    fn external() {
+        let bytes = chunk(-32, 5).to_bytes(ByteOrder::BigEndian).unwrap();
+        let file = region(&[(0, 5, 2 | EXTERNAL_FLAG, vec![])]);
+        let mut region = Region::new(Cursor::new(file.clone())).unwrap();
+        assert!(matches!(
+            region.read_chunk(0, 5),
+            Err(Error::InvalidChunk { x: 0, z: 5, .. })
+        ));
+
+        let dir = std::env::temp_dir().join(format!("mcnbt-reader-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("r.-1.0.mca");
+        fs::write(&path, file).unwrap();
+        let mut region = Region::open(&path).unwrap();
+        assert!(region.read_chunk(0, 5).is_err());
+
+        let external = dir.join("c.-32.5.mcc");
+        fs::write(&external, Compression::Zlib.compress(&bytes).unwrap()).unwrap();
+        assert_eq!(region.read_chunk(0, 5).unwrap(), Some(chunk(-32, 5)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{region_files, Entity, PoiSection, Region, REGION_WIDTH};
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The region files in one folder of a world.
+///
+/// Since Minecraft 1.17, a world stores the terrain in `region`, the
+/// entities in `entities` and the points of interest in `poi`, all of them
+/// in Anvil region files. Chunks are addressed by their absolute
+/// coordinates.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mcnbt::region::RegionSet;
+///
+/// let entities = RegionSet::entities("world");
+/// for entity in entities.read_entities(0, -3).unwrap() {
+///     println!("{} at {:?}", entity.id, entity.pos);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionSet {
+    dir: PathBuf,
+}
+
+impl RegionSet {
+    /// Creates a set of the region files in the given folder.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Creates a set of the region files storing the terrain of a world.
+    pub fn terrain(world: impl AsRef<Path>) -> Self {
+        Self::new(world.as_ref().join("region"))
+    }
+
+    /// Creates a set of the region files storing the entities of a world.
+    pub fn entities(world: impl AsRef<Path>) -> Self {
+        Self::new(world.as_ref().join("entities"))
+    }
+
+    /// Creates a set of the region files storing the points of interest of a
+    /// world.
+    pub fn poi(world: impl AsRef<Path>) -> Self {
+        Self::new(world.as_ref().join("poi"))
+    }
+
+    /// Returns the folder of the region files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the coordinates and paths of all region files ordered by
+    /// their coordinates.
+    pub fn region_files(&self) -> Result<Vec<(i32, i32, PathBuf)>, Error> {
+        region_files(&self.dir, "mca")
+    }
+
+    /// Opens the region with the given region coordinates or returns
+    /// [`None`] if it does not exist.
+    pub fn open_region(&self, x: i32, z: i32) -> Result<Option<Region<File>>, Error> {
+        match Region::open(self.dir.join(format!("r.{}.{}.mca", x, z))) {
+            Ok(region) => Ok(Some(region)),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the chunk with the given chunk coordinates or [`None`] if it
+    /// does not exist.
+    pub fn read_chunk(&self, x: i32, z: i32) -> Result<Option<Tag>, Error> {
+        let region_x = x.div_euclid(REGION_WIDTH);
+        let region_z = z.div_euclid(REGION_WIDTH);
+        match self.open_region(region_x, region_z)? {
+            Some(mut region) => region.read_chunk(x, z),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the entities of the chunk with the given chunk coordinates.
+    pub fn read_entities(&self, x: i32, z: i32) -> Result<Vec<Entity>, Error> {
+        match self.read_chunk(x, z)? {
+            Some(chunk) => Entity::from_chunk(&chunk),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the sections with points of interest of the chunk with the
+    /// given chunk coordinates.
+    pub fn read_poi(&self, x: i32, z: i32) -> Result<Vec<PoiSection>, Error> {
+        match self.read_chunk(x, z)? {
+            Some(chunk) => PoiSection::from_chunk(&chunk),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::region::entity::test::{entities, poi};
+    use std::fs;
+
+    #[test]
+    fn world() {
+        let world = std::env::temp_dir().join(format!("mcnbt-world-{}", std::process::id()));
+        fs::create_dir_all(world.join("entities")).unwrap();
+        fs::create_dir_all(world.join("poi")).unwrap();
+
+        let mut region = Region::open_writable(world.join("entities/r.0.-1.mca")).unwrap();
+        region.write_chunk(2, -1, &entities(2, -1)).unwrap();
+        let mut region = Region::open_writable(world.join("poi/r.-1.0.mca")).unwrap();
+        region.write_chunk(-1, 0, &poi()).unwrap();
+
+        let set = RegionSet::entities(&world);
+        assert_eq!(set.dir(), world.join("entities"));
+        assert_eq!(set.region_files().unwrap().len(), 1);
+        assert_eq!(
+            set.read_entities(2, -1).unwrap()[0].pos,
+            [32.5, 64.0, -16.0]
+        );
+        assert!(set.read_entities(2, 0).unwrap().is_empty());
+        assert!(set.read_entities(3, -1).unwrap().is_empty());
+
+        let set = RegionSet::poi(&world);
+        assert_eq!(set.read_poi(-1, 0).unwrap().len(), 2);
+        assert!(set.open_region(0, 0).unwrap().is_none());
+        fs::remove_dir_all(&world).unwrap();
+    }
+}
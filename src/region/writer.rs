@@ -0,0 +1,423 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{
+    external_path, index, Compression, Region, CHUNK_COUNT, EXTERNAL_FLAG, REGION_WIDTH,
+    SECTOR_SIZE,
+};
+use crate::byte_order::ByteOrder;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// The maximum amount of sectors of a chunk stored in the region file.
+const MAX_SECTOR_COUNT: usize = u8::MAX as usize;
+
+impl Region<File> {
+    /// Opens the region file at the given path for reading and writing and
+    /// creates it if it does not exist.
+    pub fn open_writable(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut region = Self::new(file)?;
+        region.path = Some(path.to_path_buf());
+        Ok(region)
+    }
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as u32)
+}
+
+/// Returns the offset of the first run of `count` free sectors. The run may
+/// extend beyond the end of the file.
+fn allocate(used: &[bool], count: usize) -> usize {
+    let mut start = 0;
+    for (i, used) in used.iter().enumerate() {
+        if *used {
+            start = i + 1;
+        } else if i + 1 - start == count {
+            break;
+        }
+    }
+    start
+}
+
+impl<R: Read + Write + Seek> Region<R> {
+    /// Compresses the chunk with [`Compression::Zlib`] and stores it.
+    ///
+    /// The chunk replaces an existing one and its timestamp is set to the
+    /// current time. See [`Region::write_raw_chunk`] for details.
+    pub fn write_chunk(&mut self, x: i32, z: i32, chunk: &Tag) -> Result<(), Error> {
+        let bytes = chunk.to_bytes(ByteOrder::BigEndian)?;
+        let data = Compression::Zlib.compress(&bytes)?;
+        self.write_raw_chunk(x, z, Compression::Zlib, &data)
+    }
+
+    /// Stores the already compressed NBT of a chunk.
+    ///
+    /// The chunk is written to the first free sectors which are large enough,
+    /// so sectors freed by other chunks are reused. The sectors of the
+    /// replaced chunk are only freed afterwards. Chunks occupying more
+    /// than 255 sectors are stored in a `c.<x>.<z>.mcc` file next to the
+    /// region file like the game does, which requires the region to be
+    /// opened with [`Region::open_writable`] from a file named
+    /// `r.<x>.<z>.mca`.
+    pub fn write_raw_chunk(
+        &mut self,
+        x: i32,
+        z: i32,
+        compression: Compression,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.store(x, z, compression, data, now())
+    }
+
+    /// Stores the compressed NBT of a chunk with the given timestamp.
+    pub(super) fn store(
+        &mut self,
+        x: i32,
+        z: i32,
+        compression: Compression,
+        data: &[u8],
+        timestamp: u32,
+    ) -> Result<(), Error> {
+        let i = index(x, z);
+        let external = self
+            .path
+            .as_deref()
+            .and_then(|path| external_path(path, x, z));
+
+        let mut sector = vec![];
+        let oversized = (data.len() + 5).div_ceil(SECTOR_SIZE) > MAX_SECTOR_COUNT;
+        if oversized {
+            let Some(external) = &external else {
+                return Err(Error::InvalidChunk {
+                    x: x.rem_euclid(REGION_WIDTH),
+                    z: z.rem_euclid(REGION_WIDTH),
+                    reason: "the file of an oversized chunk cannot be located",
+                });
+            };
+            // the previous copy is replaced at once by renaming
+            let temporary = external.with_extension("mcc.tmp");
+            fs::write(&temporary, data)?;
+            fs::rename(&temporary, external)?;
+            sector.extend(1_u32.to_be_bytes());
+            sector.push(compression.id() | EXTERNAL_FLAG);
+        } else {
+            sector.extend((data.len() as u32 + 1).to_be_bytes());
+            sector.push(compression.id());
+            sector.extend_from_slice(data);
+        }
+        let count = sector.len().div_ceil(SECTOR_SIZE);
+        sector.resize(count * SECTOR_SIZE, 0);
+
+        // the old sectors are only freed by updating the header afterwards,
+        // so the previous copy survives if writing fails
+        let offset = allocate(&self.used_sectors(), count);
+        self.inner
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.inner.write_all(&sector)?;
+        self.set_location(i, (offset as u32) << 8 | count as u32, timestamp)?;
+        match &external {
+            Some(external) if !oversized => remove_external(external),
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the chunk and returns `true` if it existed.
+    ///
+    /// The sectors of the chunk are reused by chunks written afterwards.
+    pub fn delete_chunk(&mut self, x: i32, z: i32) -> Result<bool, Error> {
+        let i = index(x, z);
+        if self.locations[i] == 0 {
+            return Ok(false);
+        }
+        // the external file is only removed once the header no longer
+        // points to it
+        self.set_location(i, 0, 0)?;
+        if let Some(external) = self
+            .path
+            .as_deref()
+            .and_then(|path| external_path(path, x, z))
+        {
+            remove_external(&external)?;
+        }
+        Ok(true)
+    }
+
+    /// Moves all chunks towards the start of the region to remove unused
+    /// sectors and returns the length of the region in bytes.
+    ///
+    /// The file is not truncated. Chunks overlapping the header or each other
+    /// make compacting fail before anything is moved, so corrupt regions must
+    /// be repaired with [`Region::repair`] first.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mcnbt::region::Region;
+    ///
+    /// let mut region = Region::open_writable("world/region/r.0.0.mca").unwrap();
+    /// region.delete_chunk(0, 0).unwrap();
+    /// let len = region.compact().unwrap();
+    /// region.get_mut().set_len(len).unwrap();
+    /// ```
+    pub fn compact(&mut self) -> Result<u64, Error> {
+        let mut chunks: Vec<usize> = (0..CHUNK_COUNT)
+            .filter(|i| self.locations[*i] != 0)
+            .collect();
+        chunks.sort_by_key(|i| self.locations[*i] >> 8);
+
+        // moving an overlapping chunk would overwrite the chunk after it
+        let mut end = 2;
+        for i in &chunks {
+            let (offset, count) = (self.locations[*i] >> 8, self.locations[*i] & 0xff);
+            if offset < end {
+                return Err(Error::InvalidChunk {
+                    x: *i as i32 % REGION_WIDTH,
+                    z: *i as i32 / REGION_WIDTH,
+                    reason: "the chunk overlaps the header or another chunk",
+                });
+            }
+            end = offset + count;
+        }
+
+        let mut next = 2;
+        for i in chunks {
+            let (offset, count) = (self.locations[i] >> 8, self.locations[i] & 0xff);
+            if offset != next {
+                let len = u64::from(count) * SECTOR_SIZE as u64;
+                let mut buf = vec![];
+                self.inner
+                    .seek(SeekFrom::Start(u64::from(offset) * SECTOR_SIZE as u64))?;
+                (&mut self.inner).take(len).read_to_end(&mut buf)?;
+                buf.resize(len as usize, 0);
+
+                self.inner
+                    .seek(SeekFrom::Start(u64::from(next) * SECTOR_SIZE as u64))?;
+                self.inner.write_all(&buf)?;
+                self.set_location(i, next << 8 | count, self.timestamps[i])?;
+            }
+            next += count;
+        }
+        Ok(u64::from(next) * SECTOR_SIZE as u64)
+    }
+
+    /// Returns which sectors are occupied by the header or by chunks.
+    fn used_sectors(&self) -> Vec<bool> {
+        let mut used = vec![true; 2];
+        for location in &self.locations {
+            if *location == 0 {
+                continue;
+            }
+            let offset = (location >> 8) as usize;
+            let end = offset + (location & 0xff) as usize;
+            if used.len() < end {
+                used.resize(end, false);
+            }
+            used[offset..end].fill(true);
+        }
+        used
+    }
+
+    /// Writes both tables of the header.
+    pub(super) fn write_header(&mut self) -> Result<(), Error> {
+        let header: Vec<u8> = self
+            .locations
+            .iter()
+            .chain(&self.timestamps)
+            .flat_map(|int| int.to_be_bytes())
+            .collect();
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner.write_all(&header)?;
+        Ok(())
+    }
+
+    /// Updates the location and the timestamp of the chunk with the given
+    /// index in the header.
+    fn set_location(&mut self, i: usize, location: u32, timestamp: u32) -> Result<(), Error> {
+        self.locations[i] = location;
+        self.timestamps[i] = timestamp;
+        self.inner.seek(SeekFrom::Start(i as u64 * 4))?;
+        self.inner.write_all(&location.to_be_bytes())?;
+        self.inner
+            .seek(SeekFrom::Start((SECTOR_SIZE + i * 4) as u64))?;
+        self.inner.write_all(&timestamp.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+/// Removes the external file of a chunk if it exists.
+fn remove_external(path: &Path) -> Result<(), Error> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::region::reader::test::chunk;
+    use crate::{byte, compound, list};
+    use std::io::Cursor;
+
+    /// Returns a chunk which occupies the given amount of sectors when it is
+    /// not compressed.
+    fn large_chunk(x: i32, z: i32, sectors: usize) -> Tag {
+        let mut chunk = chunk(x, z);
+        chunk.as_compound_mut().unwrap().push(Tag::ByteArray(
+            Some("Padding".to_string()),
+            vec![0; sectors * SECTOR_SIZE - 100],
+        ));
+        chunk
+    }
+
+    fn write_large(region: &mut Region<Cursor<Vec<u8>>>, x: i32, z: i32, sectors: usize) {
+        let bytes = large_chunk(x, z, sectors)
+            .to_bytes(ByteOrder::BigEndian)
+            .unwrap();
+        region
+            .write_raw_chunk(x, z, Compression::None, &bytes)
+            .unwrap();
+    }
+
+    #[test]
+    fn write_and_read() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        region.write_chunk(0, 0, &chunk(0, 0)).unwrap();
+        region.write_chunk(-1, 3, &chunk(-1, 3)).unwrap();
+        region.write_chunk(0, 0, &chunk(1, 1)).unwrap();
+        assert_eq!(region.chunk_info(0, 0).unwrap().sector_offset, 4);
+        assert_eq!(region.chunk_info(31, 3).unwrap().sector_offset, 3);
+        assert!(region.chunk_info(0, 0).unwrap().timestamp > 0);
+
+        let mut region = Region::new(Cursor::new(region.into_inner().into_inner())).unwrap();
+        assert_eq!(region.chunks().count(), 2);
+        assert_eq!(region.read_chunk(0, 0).unwrap(), Some(chunk(1, 1)));
+        assert_eq!(region.read_chunk(31, 3).unwrap(), Some(chunk(-1, 3)));
+        assert_eq!(region.get_ref().get_ref().len(), 5 * SECTOR_SIZE);
+
+        // the freed sectors are reused by the next chunk
+        region.write_chunk(1, 0, &chunk(1, 0)).unwrap();
+        assert_eq!(region.chunk_info(1, 0).unwrap().sector_offset, 2);
+    }
+
+    #[test]
+    fn reuse_sectors() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        write_large(&mut region, 0, 0, 2);
+        region.write_chunk(1, 0, &chunk(1, 0)).unwrap();
+        assert_eq!(region.chunk_info(1, 0).unwrap().sector_offset, 4);
+
+        // the chunk does not fit into its sectors anymore
+        write_large(&mut region, 0, 0, 4);
+        assert_eq!(region.chunk_info(0, 0).unwrap().sector_offset, 5);
+        region.write_chunk(2, 0, &chunk(2, 0)).unwrap();
+        assert_eq!(region.chunk_info(2, 0).unwrap().sector_offset, 2);
+
+        assert!(region.delete_chunk(1, 0).unwrap());
+        assert!(!region.delete_chunk(1, 0).unwrap());
+        region
+            .write_raw_chunk(3, 0, Compression::None, &[10, 0, 0, 0])
+            .unwrap();
+        assert_eq!(region.chunk_info(3, 0).unwrap().sector_offset, 3);
+        assert_eq!(region.read_chunk(3, 0).unwrap(), Some(compound!("" =>)));
+        assert_eq!(region.read_chunk(1, 0).unwrap(), None);
+        assert_eq!(region.read_chunk(0, 0).unwrap(), Some(large_chunk(0, 0, 4)));
+    }
+
+    #[test]
+    fn compact() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        for x in 0..4 {
+            write_large(&mut region, x, 0, x as usize + 1);
+        }
+        let timestamp = region.chunk_info(3, 0).unwrap().timestamp;
+        region.delete_chunk(0, 0).unwrap();
+        region.delete_chunk(2, 0).unwrap();
+
+        assert_eq!(region.compact().unwrap(), 8 * SECTOR_SIZE as u64);
+        let offsets: Vec<_> = region.chunks().map(|info| info.sector_offset).collect();
+        assert_eq!(offsets, [2, 4]);
+        assert_eq!(region.chunk_info(3, 0).unwrap().timestamp, timestamp);
+        assert_eq!(region.read_chunk(1, 0).unwrap(), Some(large_chunk(1, 0, 2)));
+        assert_eq!(region.read_chunk(3, 0).unwrap(), Some(large_chunk(3, 0, 4)));
+    }
+
+    #[test]
+    fn compact_overlapping() {
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        for x in 0..3 {
+            write_large(&mut region, x, 0, 2);
+        }
+        region.delete_chunk(0, 0).unwrap();
+
+        // chunk 2, 0 starts within the sectors of chunk 1, 0
+        let mut file = region.into_inner().into_inner();
+        file[8..12].copy_from_slice(&(5 << 8 | 2_u32).to_be_bytes());
+        let mut region = Region::new(Cursor::new(file.clone())).unwrap();
+        assert!(matches!(
+            region.compact(),
+            Err(Error::InvalidChunk { x: 2, z: 0, .. })
+        ));
+        assert_eq!(region.into_inner().into_inner(), file);
+
+        // chunk 2, 0 starts within the header
+        file[8..12].copy_from_slice(&(1 << 8 | 2_u32).to_be_bytes());
+        let mut region = Region::new(Cursor::new(file)).unwrap();
+        assert!(region.compact().is_err());
+    }
+
+    #[test]
+    fn oversized() {
+        let data = vec![0; MAX_SECTOR_COUNT * SECTOR_SIZE];
+        let mut region = Region::new(Cursor::new(vec![])).unwrap();
+        assert!(matches!(
+            region.write_raw_chunk(0, 0, Compression::None, &data),
+            Err(Error::InvalidChunk { .. })
+        ));
+
+        let dir = std::env::temp_dir().join(format!("mcnbt-writer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("r.-1.2.mca");
+        let external = dir.join("c.-31.64.mcc");
+
+        let mut region = Region::open_writable(&path).unwrap();
+        region
+            .write_raw_chunk(1, 0, Compression::None, &data)
+            .unwrap();
+        assert_eq!(fs::read(&external).unwrap(), data);
+        assert_eq!(region.chunk_info(1, 0).unwrap().sector_count, 1);
+        let mut header = [0; 5];
+        region
+            .get_mut()
+            .seek(SeekFrom::Start(2 * SECTOR_SIZE as u64))
+            .unwrap();
+        region.get_mut().read_exact(&mut header).unwrap();
+        assert_eq!(header, [0, 0, 0, 1, 131]);
+        assert_eq!(
+            region.read_raw_chunk(1, 0).unwrap(),
+            Some((Compression::None, data))
+        );
+
+        let small = compound!("" => list!("Items" => byte!(1)));
+        region.write_chunk(1, 0, &small).unwrap();
+        assert!(!external.exists());
+        drop(region);
+
+        let mut region = Region::open(&path).unwrap();
+        assert_eq!(region.read_chunk(1, 0).unwrap(), Some(small));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
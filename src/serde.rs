@@ -0,0 +1,260 @@
+//! Use NBT as a [serde] data format.
+//!
+//! Rust values are mapped onto tags as follows:
+//!
+//! | Rust                                    | Tag                        |
+//! |-----------------------------------------|----------------------------|
+//! | [`bool`], [`i8`], [`u8`]                | [`Tag::Byte`]              |
+//! | [`i16`], [`u16`]                        | [`Tag::Short`]             |
+//! | [`i32`], [`u32`]                        | [`Tag::Int`]               |
+//! | [`i64`], [`u64`]                        | [`Tag::Long`]              |
+//! | [`f32`]                                 | [`Tag::Float`]             |
+//! | [`f64`]                                 | [`Tag::Double`]            |
+//! | [`char`], [`str`], unit variants        | [`Tag::String`]            |
+//! | byte slices                             | [`Tag::ByteArray`]         |
+//! | sequences and tuples                    | [`Tag::List`]              |
+//! | structs and maps                        | [`Tag::Compound`]          |
+//!
+//! Sequences can be stored as [`Tag::ByteArray`], [`Tag::IntArray`] or
+//! [`Tag::LongArray`] instead by using the [byte_array], [int_array] and
+//! [long_array] modules or the [ByteArray], [IntArray] and [LongArray] types.
+//!
+//! Unsigned integers are stored with the bits of their signed counterpart.
+//! Fields which are [`None`] are omitted. Variants with data are stored as a
+//! compound with a single entry named after the variant.
+//!
+//! # Example
+//!
+//! ```rust
+//! use mcnbt::ByteOrder;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Item {
+//!     id: String,
+//!     #[serde(rename = "Count")]
+//!     count: i8,
+//! }
+//!
+//! let item = Item { id: "minecraft:stone".to_string(), count: 64 };
+//! let bytes = mcnbt::to_bytes(&item, ByteOrder::BigEndian).unwrap();
+//! assert_eq!(mcnbt::from_bytes::<Item>(&bytes, ByteOrder::BigEndian).unwrap(), item);
+//! ```
+
+mod array;
+mod de;
+pub(crate) mod lossless;
+mod plain;
+mod ser;
+
+use ::serde::de::DeserializeOwned;
+use ::serde::Serialize;
+use std::io::{Read, Write};
+
+use crate::byte_order::ByteOrder;
+use crate::errors::Error;
+use crate::tag::Tag;
+
+pub use array::{byte_array, int_array, long_array, ByteArray, IntArray, LongArray};
+pub use de::from_tag;
+pub use lossless::Lossless;
+pub use plain::{Plain, PlainSeed, PlainTag};
+pub use ser::to_tag;
+
+/// Serializes a value as NBT.
+///
+/// The root tag is named with an empty string.
+pub fn to_bytes<T>(value: &T, byte_order: ByteOrder) -> Result<Vec<u8>, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut tag = to_tag(value)?;
+    tag.tag_name_mut().get_or_insert_with(String::new);
+    tag.to_bytes(byte_order)
+}
+
+/// Serializes a value as NBT into a writer.
+///
+/// See also [to_bytes].
+pub fn to_writer<W, T>(mut writer: W, value: &T, byte_order: ByteOrder) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    writer.write_all(&to_bytes(value, byte_order)?)?;
+    Ok(())
+}
+
+/// Deserializes a value from NBT.
+#[cfg(feature = "read")]
+pub fn from_bytes<T>(bytes: &[u8], byte_order: ByteOrder) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_tag(Tag::from_bytes(bytes, byte_order)?)
+}
+
+/// Deserializes a value from NBT read from a reader.
+///
+/// See also [from_bytes].
+#[cfg(feature = "read")]
+pub fn from_reader<R, T>(mut reader: R, byte_order: ByteOrder) -> Result<T, Error>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    from_bytes(&bytes, byte_order)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Mode {
+        Survival,
+        Spectating { target: String },
+        Teleporting(f64, f64, f64),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Player {
+        name: String,
+        health: f32,
+        level: i32,
+        xp: u64,
+        on_ground: bool,
+        spawn: Option<(i32, i32, i32)>,
+        last_death: Option<i64>,
+        inventory: Vec<HashMap<String, i16>>,
+        mode: Mode,
+        history: Vec<Mode>,
+        #[serde(with = "long_array")]
+        block_states: Vec<i64>,
+        #[serde(with = "int_array")]
+        uuid: [i32; 4],
+        #[serde(with = "byte_array")]
+        light: Option<Vec<u8>>,
+        biomes: ByteArray<'static>,
+    }
+
+    fn player() -> Player {
+        Player {
+            name: "Steve".to_string(),
+            health: 20.0,
+            level: 30,
+            xp: u64::MAX,
+            on_ground: true,
+            spawn: Some((0, 64, 0)),
+            last_death: None,
+            inventory: vec![HashMap::from([("Count".to_string(), 64)])],
+            mode: Mode::Survival,
+            history: vec![
+                Mode::Spectating {
+                    target: "Alex".to_string(),
+                },
+                Mode::Teleporting(0.5, 64.0, 0.5),
+            ],
+            block_states: vec![i64::MIN, 0, i64::MAX],
+            uuid: [1, 2, 3, 4],
+            light: Some(vec![0xff, 0x0f]),
+            biomes: ByteArray::from(vec![]),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for byte_order in [ByteOrder::BigEndian, ByteOrder::LittleEndian] {
+            let bytes = to_bytes(&player(), byte_order).unwrap();
+            assert_eq!(from_bytes::<Player>(&bytes, byte_order).unwrap(), player());
+        }
+    }
+
+    #[test]
+    fn tag_types() {
+        let tag = to_tag(&player()).unwrap();
+        assert_eq!(
+            tag["name"],
+            Tag::String(Some("name".to_string()), "Steve".to_string())
+        );
+        assert_eq!(tag["health"], Tag::Float(Some("health".to_string()), 20.0));
+        assert_eq!(tag["xp"], Tag::Long(Some("xp".to_string()), -1));
+        assert_eq!(
+            tag["on_ground"],
+            Tag::Byte(Some("on_ground".to_string()), 1)
+        );
+        assert_eq!(tag["spawn"][1], Tag::Int(None, 64));
+        assert_eq!(tag.get("last_death"), None);
+        assert_eq!(
+            tag["mode"],
+            Tag::String(Some("mode".to_string()), "Survival".to_string())
+        );
+        assert_eq!(
+            tag["history"][0]["Spectating"]["target"].as_str(),
+            Some("Alex")
+        );
+        assert_eq!(tag["history"][1]["Teleporting"][1], Tag::Double(None, 64.0));
+        assert_eq!(
+            tag["block_states"].as_long_array(),
+            Some([i64::MIN, 0, i64::MAX].as_slice())
+        );
+        assert_eq!(tag["uuid"].as_int_array(), Some([1, 2, 3, 4].as_slice()));
+        assert_eq!(tag["light"].as_byte_array(), Some([-1, 15].as_slice()));
+        assert_eq!(tag["biomes"].as_byte_array(), Some([].as_slice()));
+    }
+
+    #[test]
+    fn heterogeneous_list() {
+        #[derive(Serialize)]
+        #[serde(untagged)]
+        enum Value {
+            Int(i32),
+            String(String),
+        }
+
+        assert!(matches!(
+            to_tag(&vec![Value::Int(1), Value::String("2".to_string())]),
+            Err(Error::HeterogeneousList)
+        ));
+    }
+
+    #[test]
+    fn array_elements() {
+        #[derive(Serialize)]
+        struct Section {
+            #[serde(with = "int_array")]
+            data: Vec<i64>,
+        }
+
+        assert!(matches!(
+            to_tag(&Section { data: vec![1] }),
+            Err(Error::UnexpectedType {
+                expected: "TAG_Int",
+                found: "TAG_Long"
+            })
+        ));
+    }
+
+    #[test]
+    fn borrowed_array() {
+        let bytes = [1, 2, 3];
+        assert_eq!(
+            to_tag(&ByteArray::from(bytes.as_slice())).unwrap(),
+            Tag::ByteArray(None, vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn writer_and_reader() {
+        let mut buf = vec![];
+        to_writer(&mut buf, &player(), ByteOrder::BigEndian).unwrap();
+        assert_eq!(
+            from_reader::<_, Player>(buf.as_slice(), ByteOrder::BigEndian).unwrap(),
+            player()
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Helpers for storing sequences as array tags instead of lists.
+
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::ops::Deref;
+
+/// Name of the newtype struct marking a sequence as a [`crate::Tag::ByteArray`].
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "$mcnbt::ByteArray";
+
+/// Name of the newtype struct marking a sequence as a [`crate::Tag::IntArray`].
+pub(crate) const INT_ARRAY_TOKEN: &str = "$mcnbt::IntArray";
+
+/// Name of the newtype struct marking a sequence as a [`crate::Tag::LongArray`].
+pub(crate) const LONG_ARRAY_TOKEN: &str = "$mcnbt::LongArray";
+
+macro_rules! array_module {
+    ($module:ident, $token:ident, $tag:literal, $element:literal) => {
+        #[doc = concat!("Stores a sequence of `", $element, "` as a [`crate::Tag::", $tag, "`].")]
+        ///
+        /// Use it with `#[serde(with = "...")]`. Formats other than NBT see a
+        /// plain sequence.
+        pub mod $module {
+            use super::*;
+
+            #[doc = concat!("Serializes a sequence as a [`crate::Tag::", $tag, "`].")]
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: Serialize + ?Sized,
+                S: Serializer,
+            {
+                serializer.serialize_newtype_struct($token, value)
+            }
+
+            #[doc = concat!("Deserializes a sequence from a [`crate::Tag::", $tag, "`].")]
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                T: Deserialize<'de>,
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+array_module!(byte_array, BYTE_ARRAY_TOKEN, "ByteArray", "i8");
+array_module!(int_array, INT_ARRAY_TOKEN, "IntArray", "i32");
+array_module!(long_array, LONG_ARRAY_TOKEN, "LongArray", "i64");
+
+macro_rules! array_type {
+    ($name:ident, $module:ident, $element:ty) => {
+        #[doc = concat!("A sequence of [`", stringify!($element), "`] stored as a [`crate::Tag::", stringify!($name), "`].")]
+        ///
+        /// The elements can either be borrowed or owned.
+        #[derive(Clone, Debug, Default, PartialEq)]
+        pub struct $name<'a>(pub Cow<'a, [$element]>);
+
+        impl $name<'_> {
+            /// Returns the elements, cloning them if they are borrowed.
+            pub fn into_owned(self) -> Vec<$element> {
+                self.0.into_owned()
+            }
+        }
+
+        impl Deref for $name<'_> {
+            type Target = [$element];
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl<'a> From<&'a [$element]> for $name<'a> {
+            fn from(value: &'a [$element]) -> Self {
+                $name(Cow::Borrowed(value))
+            }
+        }
+
+        impl From<Vec<$element>> for $name<'_> {
+            fn from(value: Vec<$element>) -> Self {
+                $name(Cow::Owned(value))
+            }
+        }
+
+        impl Serialize for $name<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                $module::serialize(&*self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name<'_> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                $module::deserialize::<Vec<$element>, D>(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+
+array_type!(ByteArray, byte_array, i8);
+array_type!(IntArray, int_array, i32);
+array_type!(LongArray, long_array, i64);
@@ -0,0 +1,278 @@
+use ::serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use ::serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use ::serde::forward_to_deserialize_any;
+
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Deserializes a value from a tag.
+///
+/// The name of the tag itself is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, int_array};
+///
+/// let ints: Vec<u32> = mcnbt::serde::from_tag(int_array!(1, 2, 3)).unwrap();
+/// assert_eq!(ints, vec![1, 2, 3]);
+/// ```
+pub fn from_tag<T>(tag: Tag) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(tag)
+}
+
+/// Turns the children of a compound into map entries keyed by their names.
+fn entries(tags: Vec<Tag>) -> impl Iterator<Item = (String, Tag)> {
+    tags.into_iter().map(|mut tag| {
+        let name = tag.tag_name_mut().take().unwrap_or_default();
+        (name, tag)
+    })
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Tag {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Tag {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // elements of arrays are deserialized as tags as well so that
+        // unsigned integers are reinterpreted the same way as elsewhere
+        match self {
+            Tag::Byte(_, payload) => visitor.visit_i8(payload),
+            Tag::Short(_, payload) => visitor.visit_i16(payload),
+            Tag::Int(_, payload) => visitor.visit_i32(payload),
+            Tag::Long(_, payload) => visitor.visit_i64(payload),
+            Tag::Float(_, payload) => visitor.visit_f32(payload),
+            Tag::Double(_, payload) => visitor.visit_f64(payload),
+            Tag::ByteArray(_, payload) => {
+                SeqDeserializer::new(payload.into_iter().map(Tag::from)).deserialize_any(visitor)
+            }
+            Tag::String(_, payload) => visitor.visit_string(payload),
+            Tag::List(_, payload) => {
+                SeqDeserializer::new(payload.into_iter()).deserialize_any(visitor)
+            }
+            Tag::Compound(_, payload) => {
+                MapDeserializer::new(entries(payload)).deserialize_any(visitor)
+            }
+            Tag::IntArray(_, payload) => {
+                SeqDeserializer::new(payload.into_iter().map(Tag::from)).deserialize_any(visitor)
+            }
+            Tag::LongArray(_, payload) => {
+                SeqDeserializer::new(payload.into_iter().map(Tag::from)).deserialize_any(visitor)
+            }
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::Byte(_, payload) => visitor.visit_bool(payload != 0),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::Byte(_, payload) => visitor.visit_u8(payload as u8),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::Short(_, payload) => visitor.visit_u16(payload as u16),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::Int(_, payload) => visitor.visit_u32(payload as u32),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::Long(_, payload) => visitor.visit_u64(payload as u64),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::ByteArray(_, payload) => {
+                visitor.visit_byte_buf(payload.into_iter().map(|byte| byte as u8).collect())
+            }
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // absent values are handled by the containing compound
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Tag::String(_, variant) => {
+                let variant: StringDeserializer<Error> = variant.into_deserializer();
+                visitor.visit_enum(variant)
+            }
+            Tag::Compound(_, payload) if payload.len() == 1 => {
+                let (variant, value) = entries(payload).next().unwrap();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            other => Err(Error::Custom(format!(
+                "expected a TAG_String or a TAG_Compound with a single entry for an enum but found {}",
+                other.name()
+            ))),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string
+        seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Deserializes an enum variant stored as a compound with a single entry.
+struct EnumDeserializer {
+    variant: String,
+    value: Tag,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant: StringDeserializer<Error> = self.variant.into_deserializer();
+        Ok((seed.deserialize(variant)?, VariantDeserializer(self.value)))
+    }
+}
+
+struct VariantDeserializer(Tag);
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.0)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.0, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.0, visitor)
+    }
+}
@@ -0,0 +1,282 @@
+//! A variant of the typed representation of tags which survives JSON.
+
+use ::serde::de::{self, Visitor};
+use ::serde::ser::{SerializeMap, SerializeSeq};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::tag::Tag;
+
+/// Serializes a tag like its [`Serialize`] implementation but without losing
+/// information in formats like JSON.
+///
+/// The representation differs in the following ways:
+///
+/// - the payloads of [`Tag::Long`] and the elements of [`Tag::LongArray`]
+///   are strings because JavaScript cannot represent integers above 2^53
+/// - non-finite payloads of [`Tag::Float`] and [`Tag::Double`] are the
+///   strings `"NaN"`, `"Infinity"` and `"-Infinity"` because JSON has no
+///   literals for them
+///
+/// The [`Deserialize`] implementation of [`Tag`] accepts both
+/// representations, so no dedicated counterpart is needed for reading.
+///
+/// All payloads of NaN are written as `"NaN"` regardless of their bits.
+/// Strings which are not valid Unicode are rejected by [`Tag::from_bytes`]
+/// already and hence never reach this representation.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, compound, double, long};
+/// use mcnbt::serde::Lossless;
+///
+/// let tag = compound!(long!("Seed" => i64::MAX), double!("Motion" => f64::NAN));
+/// let json = serde_json::to_value(Lossless(&tag)).unwrap();
+///
+/// assert_eq!(json["payload"][0]["payload"], "9223372036854775807");
+/// assert_eq!(json["payload"][1]["payload"], "NaN");
+/// assert_eq!(serde_json::from_value::<Tag>(json).unwrap().pretty(), tag.pretty());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Lossless<'a>(pub &'a Tag);
+
+/// Returns the string representing a non-finite floating point number.
+fn non_finite(value: f64) -> Option<&'static str> {
+    if value.is_nan() {
+        Some("NaN")
+    } else if value == f64::INFINITY {
+        Some("Infinity")
+    } else if value == f64::NEG_INFINITY {
+        Some("-Infinity")
+    } else {
+        None
+    }
+}
+
+impl Serialize for Lossless<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        struct Payload<'a>(&'a Tag);
+
+        impl Serialize for Payload<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                match self.0 {
+                    Tag::Byte(_, payload) => serializer.serialize_i8(*payload),
+                    Tag::Short(_, payload) => serializer.serialize_i16(*payload),
+                    Tag::Int(_, payload) => serializer.serialize_i32(*payload),
+                    Tag::Long(_, payload) => serializer.serialize_str(&payload.to_string()),
+                    Tag::Float(_, payload) => match non_finite((*payload).into()) {
+                        Some(value) => serializer.serialize_str(value),
+                        None => serializer.serialize_f32(*payload),
+                    },
+                    Tag::Double(_, payload) => match non_finite(*payload) {
+                        Some(value) => serializer.serialize_str(value),
+                        None => serializer.serialize_f64(*payload),
+                    },
+                    Tag::ByteArray(_, payload) => payload.serialize(serializer),
+                    Tag::String(_, payload) => serializer.serialize_str(payload),
+                    Tag::List(_, payload) | Tag::Compound(_, payload) => {
+                        let mut seq = serializer.serialize_seq(Some(payload.len()))?;
+                        for tag in payload {
+                            seq.serialize_element(&Lossless(tag))?;
+                        }
+                        seq.end()
+                    }
+                    Tag::IntArray(_, payload) => payload.serialize(serializer),
+                    Tag::LongArray(_, payload) => {
+                        let mut seq = serializer.serialize_seq(Some(payload.len()))?;
+                        for long in payload {
+                            seq.serialize_element(&long.to_string())?;
+                        }
+                        seq.end()
+                    }
+                }
+            }
+        }
+
+        let tag_type = match self.0 {
+            Tag::Byte(..) => "byte",
+            Tag::Short(..) => "short",
+            Tag::Int(..) => "int",
+            Tag::Long(..) => "long",
+            Tag::Float(..) => "float",
+            Tag::Double(..) => "double",
+            Tag::ByteArray(..) => "byte_array",
+            Tag::String(..) => "string",
+            Tag::List(..) => "list",
+            Tag::Compound(..) => "compound",
+            Tag::IntArray(..) => "int_array",
+            Tag::LongArray(..) => "long_array",
+        };
+
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("type", tag_type)?;
+        map.serialize_entry("name", &self.0.tag_name())?;
+        map.serialize_entry("payload", &Payload(self.0))?;
+        map.end()
+    }
+}
+
+/// Parses an integer written as a string.
+pub(crate) fn parse_long<E: de::Error>(value: &str) -> Result<i64, E> {
+    value
+        .parse()
+        .map_err(|_| E::custom(format!("invalid integer \"{}\"", value)))
+}
+
+/// Parses a floating point number written as a string.
+pub(crate) fn parse_float<E: de::Error>(value: &str) -> Result<f64, E> {
+    match value {
+        "NaN" => Ok(f64::NAN),
+        "Infinity" => Ok(f64::INFINITY),
+        "-Infinity" => Ok(f64::NEG_INFINITY),
+        _ => value
+            .parse()
+            .map_err(|_| E::custom(format!("invalid floating point number \"{}\"", value))),
+    }
+}
+
+/// An integer which is either a number or a string.
+pub(crate) struct LongRepr(pub(crate) i64);
+
+impl<'de> Deserialize<'de> for LongRepr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LongVisitor;
+
+        impl Visitor<'_> for LongVisitor {
+            type Value = LongRepr;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer or a string containing an integer")
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(LongRepr(v))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                i64::try_from(v)
+                    .map(LongRepr)
+                    .map_err(|_| E::custom(format!("integer {} out of range", v)))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse_long(v).map(LongRepr)
+            }
+        }
+
+        deserializer.deserialize_any(LongVisitor)
+    }
+}
+
+/// A floating point number which is either a number or a string.
+pub(crate) struct FloatRepr(pub(crate) f64);
+
+impl<'de> Deserialize<'de> for FloatRepr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FloatVisitor;
+
+        impl Visitor<'_> for FloatVisitor {
+            type Value = FloatRepr;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number or a string containing a number")
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(FloatRepr(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(FloatRepr(v as f64))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(FloatRepr(v as f64))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse_float(v).map(FloatRepr)
+            }
+        }
+
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compound, string, ByteOrder};
+
+    /// Compares tags by their bits so that NaN equals itself.
+    fn assert_identical(a: &Tag, b: &Tag) {
+        match (a, b) {
+            (Tag::Float(x, a), Tag::Float(y, b)) => {
+                assert_eq!(x, y);
+                assert!(a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan()));
+            }
+            (Tag::Double(x, a), Tag::Double(y, b)) => {
+                assert_eq!(x, y);
+                assert!(a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan()));
+            }
+            (Tag::List(x, a), Tag::List(y, b)) | (Tag::Compound(x, a), Tag::Compound(y, b)) => {
+                assert_eq!(x, y);
+                assert_eq!(a.len(), b.len());
+                a.iter().zip(b).for_each(|(a, b)| assert_identical(a, b));
+            }
+            _ => assert_eq!(a, b),
+        }
+    }
+
+    fn round_trip(tag: &Tag) -> Tag {
+        let json = serde_json::to_string(&Lossless(tag)).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn bigtest() {
+        let tag = Tag::from_bytes(
+            include_bytes!("../../examples/bigtest.nbt"),
+            ByteOrder::BigEndian,
+        )
+        .unwrap();
+        assert_identical(&round_trip(&tag), &tag);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let tag = compound!(
+            Tag::Long(Some("max".to_string()), i64::MAX),
+            Tag::Long(Some("min".to_string()), i64::MIN),
+            Tag::LongArray(Some("longs".to_string()), vec![i64::MAX, -1]),
+            Tag::Float(Some("nan".to_string()), f32::NAN),
+            Tag::Float(Some("inf".to_string()), f32::INFINITY),
+            Tag::Double(Some("-inf".to_string()), f64::NEG_INFINITY),
+            Tag::Double(Some("-0".to_string()), -0.0),
+            Tag::Float(Some("tiny".to_string()), f32::MIN_POSITIVE),
+            string!("nul" => "\0\u{10ffff}"),
+            Tag::List(Some("empty".to_string()), vec![]),
+        );
+        assert_identical(&round_trip(&tag), &tag);
+    }
+
+    #[test]
+    fn regular_representation_is_accepted() {
+        let tag = compound!(Tag::Long(Some("small".to_string()), 42));
+        let json = serde_json::to_string(&tag).unwrap();
+        assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag);
+    }
+}
@@ -0,0 +1,426 @@
+//! A natural representation of tags without type annotations.
+
+use ::serde::de::{self, DeserializeSeed, Visitor};
+use ::serde::ser::{SerializeMap, SerializeSeq};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::mem::discriminant;
+
+use crate::tag::Tag;
+
+/// Serializes a tag without its type and name.
+///
+/// Compounds become maps, lists and arrays become sequences and every other
+/// tag becomes its payload. The name of the tag itself is omitted.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, compound, float, int, list};
+/// use mcnbt::serde::Plain;
+///
+/// let tag = compound!(
+///     float!("Health" => 20.0),
+///     list!("Inventory" => int!(1), int!(2)),
+/// );
+///
+/// assert_eq!(
+///     serde_json::to_value(Plain(&tag)).unwrap(),
+///     serde_json::json!({ "Health": 20.0, "Inventory": [1, 2] })
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Plain<'a>(pub &'a Tag);
+
+impl Serialize for Plain<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Tag::Byte(_, payload) => serializer.serialize_i8(*payload),
+            Tag::Short(_, payload) => serializer.serialize_i16(*payload),
+            Tag::Int(_, payload) => serializer.serialize_i32(*payload),
+            Tag::Long(_, payload) => serializer.serialize_i64(*payload),
+            Tag::Float(_, payload) => serializer.serialize_f32(*payload),
+            Tag::Double(_, payload) => serializer.serialize_f64(*payload),
+            Tag::ByteArray(_, payload) => payload.serialize(serializer),
+            Tag::String(_, payload) => serializer.serialize_str(payload),
+            Tag::List(_, payload) => {
+                let mut seq = serializer.serialize_seq(Some(payload.len()))?;
+                for tag in payload {
+                    seq.serialize_element(&Plain(tag))?;
+                }
+                seq.end()
+            }
+            Tag::Compound(_, payload) => {
+                let mut map = serializer.serialize_map(Some(payload.len()))?;
+                for tag in payload {
+                    map.serialize_entry(tag.tag_name().unwrap_or_default(), &Plain(tag))?;
+                }
+                map.end()
+            }
+            Tag::IntArray(_, payload) => payload.serialize(serializer),
+            Tag::LongArray(_, payload) => payload.serialize(serializer),
+        }
+    }
+}
+
+/// Deserializes a tag from its plain representation.
+///
+/// Without a schema the types are guessed:
+///
+/// - booleans become a [`Tag::Byte`]
+/// - integers become a [`Tag::Int`] or a [`Tag::Long`] if they are too large
+/// - floating point numbers become a [`Tag::Double`]
+/// - strings become a [`Tag::String`]
+/// - sequences become a [`Tag::List`]; mixed numbers are widened to a
+///   common type
+/// - maps become a [`Tag::Compound`]
+///
+/// With a schema, which is any tag of the expected shape, the types of the
+/// schema are used wherever the schema has a counterpart. Lists use their
+/// first element as the schema for all elements.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, long_array};
+/// use mcnbt::serde::PlainSeed;
+/// use serde::de::DeserializeSeed;
+///
+/// let schema = compound!(byte!("Count" => 0), long_array!("data" =>));
+/// let value = serde_json::json!({ "Count": 64, "data": [1, 2], "id": "minecraft:stone" });
+///
+/// assert_eq!(
+///     PlainSeed::with_schema(&schema).deserialize(value).unwrap(),
+///     compound!(
+///         byte!("Count" => 64),
+///         long_array!("data" => 1, 2),
+///         Tag::String(Some("id".to_string()), "minecraft:stone".to_string()),
+///     )
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlainSeed<'a> {
+    schema: Option<&'a Tag>,
+}
+
+impl<'a> PlainSeed<'a> {
+    /// Creates a seed which guesses all types.
+    pub fn new() -> Self {
+        Self { schema: None }
+    }
+
+    /// Creates a seed which takes the types from `schema`.
+    pub fn with_schema(schema: &'a Tag) -> Self {
+        Self {
+            schema: Some(schema),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for PlainSeed<'_> {
+    type Value = Tag;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut tag = deserializer.deserialize_any(PlainVisitor {
+            schema: self.schema,
+        })?;
+        *tag.tag_name_mut() = self.schema.and_then(Tag::tag_name).map(String::from);
+        Ok(tag)
+    }
+}
+
+/// A tag deserialized from its plain representation by guessing its types.
+///
+/// See [PlainSeed] for how types are guessed and for using a schema instead.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::Tag;
+/// use mcnbt::serde::PlainTag;
+///
+/// let PlainTag(tag) = serde_json::from_str(r#"{ "Pos": [0.5, 64, 0.5] }"#).unwrap();
+/// assert_eq!(
+///     tag["Pos"],
+///     Tag::List(
+///         Some("Pos".to_string()),
+///         vec![Tag::Double(None, 0.5), Tag::Double(None, 64.0), Tag::Double(None, 0.5)]
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlainTag(pub Tag);
+
+impl<'de> Deserialize<'de> for PlainTag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PlainSeed::new().deserialize(deserializer).map(PlainTag)
+    }
+}
+
+struct PlainVisitor<'a> {
+    schema: Option<&'a Tag>,
+}
+
+impl PlainVisitor<'_> {
+    fn mismatch<E: de::Error>(&self, found: &str) -> E {
+        E::custom(format!(
+            "expected a value for {} but found {}",
+            self.schema.map(Tag::name).unwrap_or("a tag"),
+            found
+        ))
+    }
+
+    fn int<T: TryFrom<i64>, E: de::Error>(&self, value: i64) -> Result<T, E> {
+        T::try_from(value).map_err(|_| {
+            E::custom(format!(
+                "integer {} is out of range for {}",
+                value,
+                self.schema.map(Tag::name).unwrap_or("a tag")
+            ))
+        })
+    }
+}
+
+impl<'de> Visitor<'de> for PlainVisitor<'_> {
+    type Value = Tag;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.schema {
+            Some(schema) => write!(formatter, "a value for {}", schema.name()),
+            None => formatter.write_str("a boolean, number, string, sequence or map"),
+        }
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        match self.schema {
+            None | Some(Tag::Byte(..)) => Ok(Tag::Byte(None, v.into())),
+            Some(_) => Err(self.mismatch("a boolean")),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(match self.schema {
+            None => match i32::try_from(v) {
+                Ok(v) => Tag::Int(None, v),
+                Err(_) => Tag::Long(None, v),
+            },
+            Some(Tag::Byte(..)) => Tag::Byte(None, self.int(v)?),
+            Some(Tag::Short(..)) => Tag::Short(None, self.int(v)?),
+            Some(Tag::Int(..)) => Tag::Int(None, self.int(v)?),
+            Some(Tag::Long(..)) => Tag::Long(None, v),
+            Some(Tag::Float(..)) => Tag::Float(None, v as f32),
+            Some(Tag::Double(..)) => Tag::Double(None, v as f64),
+            Some(_) => return Err(self.mismatch("an integer")),
+        })
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        let v = i64::try_from(v)
+            .map_err(|_| E::custom(format!("integer {} is out of range for NBT", v)))?;
+        self.visit_i64(v)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        match self.schema {
+            None | Some(Tag::Double(..)) => Ok(Tag::Double(None, v)),
+            Some(Tag::Float(..)) => Ok(Tag::Float(None, v as f32)),
+            Some(_) => Err(self.mismatch("a floating point number")),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_string(v.to_string())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        match self.schema {
+            None | Some(Tag::String(..)) => Ok(Tag::String(None, v)),
+            Some(_) => Err(self.mismatch("a string")),
+        }
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Err(E::custom("NBT cannot represent null"))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visit_unit()
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        fn elements<'de, A>(mut seq: A, schema: Tag) -> Result<Vec<Tag>, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            let mut tags = vec![];
+            while let Some(tag) = seq.next_element_seed(PlainSeed::with_schema(&schema))? {
+                tags.push(tag);
+            }
+            Ok(tags)
+        }
+
+        match self.schema {
+            Some(Tag::ByteArray(..)) => Ok(Tag::ByteArray(
+                None,
+                elements(seq, Tag::Byte(None, 0))?
+                    .iter()
+                    .filter_map(Tag::as_byte)
+                    .collect(),
+            )),
+            Some(Tag::IntArray(..)) => Ok(Tag::IntArray(
+                None,
+                elements(seq, Tag::Int(None, 0))?
+                    .iter()
+                    .filter_map(Tag::as_int)
+                    .collect(),
+            )),
+            Some(Tag::LongArray(..)) => Ok(Tag::LongArray(
+                None,
+                elements(seq, Tag::Long(None, 0))?
+                    .iter()
+                    .filter_map(Tag::as_long)
+                    .collect(),
+            )),
+            Some(Tag::List(_, schema)) if !schema.is_empty() => {
+                let mut tags = vec![];
+                while let Some(tag) = seq.next_element_seed(PlainSeed::with_schema(&schema[0]))? {
+                    tags.push(tag);
+                }
+                Ok(Tag::List(None, tags))
+            }
+            None | Some(Tag::List(..)) => {
+                let mut tags = vec![];
+                while let Some(tag) = seq.next_element_seed(PlainSeed::new())? {
+                    tags.push(tag);
+                }
+                unify(tags)
+                    .map(|tags| Tag::List(None, tags))
+                    .ok_or_else(|| {
+                        de::Error::custom("all elements of a list must be of the same type")
+                    })
+            }
+            Some(_) => Err(self.mismatch("a sequence")),
+        }
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        match self.schema {
+            None | Some(Tag::Compound(..)) => {
+                let mut tags = vec![];
+                while let Some(name) = map.next_key::<String>()? {
+                    let schema = self.schema.and_then(|schema| schema.get(&name));
+                    let mut tag = map.next_value_seed(PlainSeed { schema })?;
+                    *tag.tag_name_mut() = Some(name);
+                    tags.push(tag);
+                }
+                Ok(Tag::Compound(None, tags))
+            }
+            Some(_) => Err(self.mismatch("a map")),
+        }
+    }
+}
+
+/// Widens the guessed types of list elements to a common type.
+///
+/// Returns [`None`] if the elements cannot share a type.
+fn unify(tags: Vec<Tag>) -> Option<Vec<Tag>> {
+    let Some(first) = tags.first() else {
+        return Some(tags);
+    };
+    if tags
+        .iter()
+        .all(|tag| discriminant(tag) == discriminant(first))
+    {
+        return Some(tags);
+    }
+    if tags.iter().any(|tag| tag.as_f64_lossy().is_none()) {
+        return None;
+    }
+    if tags
+        .iter()
+        .any(|tag| matches!(tag, Tag::Float(..) | Tag::Double(..)))
+    {
+        tags.iter()
+            .map(|tag| tag.as_f64_lossy().map(|v| Tag::Double(None, v)))
+            .collect()
+    } else {
+        tags.iter()
+            .map(|tag| tag.as_i64_lossy().map(|v| Tag::Long(None, v)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list, string};
+
+    #[test]
+    fn round_trip_with_schema() {
+        let tag = compound!(
+            byte!("Count" => 1),
+            string!("id" => "minecraft:stone"),
+            list!("Pos" => Tag::Float(None, 0.5), Tag::Float(None, 64.0)),
+            Tag::IntArray(Some("UUID".to_string()), vec![1, 2, 3, 4]),
+            compound!("tag" => Tag::Short(Some("Damage".to_string()), 3)),
+        );
+        let json = serde_json::to_string(&Plain(&tag)).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        assert_eq!(
+            PlainSeed::with_schema(&tag)
+                .deserialize(&mut deserializer)
+                .unwrap(),
+            tag
+        );
+    }
+
+    #[test]
+    fn guessed_types() {
+        let PlainTag(tag) = serde_json::from_value(serde_json::json!({
+            "small": 1,
+            "large": 4_294_967_296_i64,
+            "flag": true,
+            "mixed": [1, 4_294_967_296_i64],
+            "empty": [],
+        }))
+        .unwrap();
+        assert_eq!(
+            tag,
+            compound!(
+                int!("small" => 1),
+                Tag::Long(Some("large".to_string()), 4_294_967_296),
+                byte!("flag" => 1),
+                list!("mixed" => Tag::Long(None, 1), Tag::Long(None, 4_294_967_296)),
+                list!("empty" =>),
+            )
+        );
+    }
+
+    #[test]
+    fn mismatches() {
+        let schema = compound!(byte!("Count" => 0));
+        assert!(PlainSeed::with_schema(&schema)
+            .deserialize(serde_json::json!({ "Count": 300 }))
+            .is_err());
+        assert!(PlainSeed::with_schema(&schema)
+            .deserialize(serde_json::json!({ "Count": "64" }))
+            .is_err());
+        assert!(serde_json::from_str::<PlainTag>(r#"[1, "2"]"#).is_err());
+        assert!(serde_json::from_str::<PlainTag>("null").is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use ::serde::ser::{self, Serialize};
+use std::mem::discriminant;
+
+use super::array::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN};
+use crate::errors::Error;
+use crate::tag::Tag;
+
+/// Serializes a value into an unnamed tag.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::Tag;
+///
+/// assert_eq!(
+///     mcnbt::serde::to_tag(&vec![1_i8, 2, 3]).unwrap(),
+///     Tag::List(None, vec![Tag::Byte(None, 1), Tag::Byte(None, 2), Tag::Byte(None, 3)])
+/// );
+/// ```
+pub fn to_tag<T>(value: &T) -> Result<Tag, Error>
+where
+    T: Serialize + ?Sized,
+{
+    value
+        .serialize(Serializer)?
+        .ok_or_else(|| Error::Custom("cannot serialize `None` as a tag".to_string()))
+}
+
+/// Gives a tag a name.
+fn named(mut tag: Tag, name: impl Into<String>) -> Tag {
+    *tag.tag_name_mut() = Some(name.into());
+    tag
+}
+
+/// Converts a list into the array tag requested by one of the tokens of
+/// [`super::array`].
+fn into_array(token: &'static str, tag: Tag) -> Result<Tag, Error> {
+    fn elements<T>(
+        tags: Vec<Tag>,
+        expected: &'static str,
+        payload: fn(&Tag) -> Option<T>,
+    ) -> Result<Vec<T>, Error> {
+        tags.iter()
+            .map(|tag| {
+                payload(tag).ok_or(Error::UnexpectedType {
+                    expected,
+                    found: tag.name(),
+                })
+            })
+            .collect()
+    }
+
+    Ok(match (token, tag) {
+        (BYTE_ARRAY_TOKEN, tag @ Tag::ByteArray(..)) => tag,
+        (BYTE_ARRAY_TOKEN, Tag::List(_, tags)) => {
+            Tag::ByteArray(None, elements(tags, "TAG_Byte", Tag::as_byte)?)
+        }
+        (INT_ARRAY_TOKEN, Tag::List(_, tags)) => {
+            Tag::IntArray(None, elements(tags, "TAG_Int", Tag::as_int)?)
+        }
+        (LONG_ARRAY_TOKEN, Tag::List(_, tags)) => {
+            Tag::LongArray(None, elements(tags, "TAG_Long", Tag::as_long)?)
+        }
+        (_, tag) => {
+            return Err(Error::UnexpectedType {
+                expected: "TAG_List",
+                found: tag.name(),
+            })
+        }
+    })
+}
+
+/// Serializes a value into a tag.
+///
+/// The output is [`None`] for [`Option::None`] so that such fields can be
+/// omitted from compounds.
+pub(crate) struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = ListSerializer;
+    type SerializeMap = CompoundSerializer;
+    type SerializeStruct = CompoundSerializer;
+    type SerializeStructVariant = CompoundSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Byte(None, v.into())))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Byte(None, v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Short(None, v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Int(None, v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Long(None, v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Byte(None, v as i8)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Short(None, v as i16)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Int(None, v as i32)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Long(None, v as i64)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Float(None, v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Double(None, v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::String(None, v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::String(None, v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::ByteArray(
+            None,
+            v.iter().map(|byte| *byte as i8).collect(),
+        )))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(Tag::Compound(None, vec![])))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let tag = value.serialize(self)?;
+        match name {
+            BYTE_ARRAY_TOKEN | INT_ARRAY_TOKEN | LONG_ARRAY_TOKEN => {
+                tag.map(|tag| into_array(name, tag)).transpose()
+            }
+            _ => Ok(tag),
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Some(Tag::Compound(
+            None,
+            vec![named(to_tag(value)?, variant)],
+        )))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(ListSerializer {
+            variant: None,
+            tags: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(ListSerializer {
+            variant: Some(variant),
+            tags: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(CompoundSerializer {
+            variant: None,
+            tags: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(CompoundSerializer {
+            variant: Some(variant),
+            tags: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Collects the elements of a [`Tag::List`].
+pub(crate) struct ListSerializer {
+    /// Name of the enum variant the list belongs to, if any.
+    variant: Option<&'static str>,
+    tags: Vec<Tag>,
+}
+
+impl ListSerializer {
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let tag = to_tag(value)?;
+        if let Some(first) = self.tags.first() {
+            if discriminant(first) != discriminant(&tag) {
+                return Err(Error::HeterogeneousList);
+            }
+        }
+        self.tags.push(tag);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Tag>, Error> {
+        let list = Tag::List(None, self.tags);
+        Ok(Some(match self.variant {
+            Some(variant) => Tag::Compound(None, vec![named(list, variant)]),
+            None => list,
+        }))
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Collects the entries of a [`Tag::Compound`].
+pub(crate) struct CompoundSerializer {
+    /// Name of the enum variant the compound belongs to, if any.
+    variant: Option<&'static str>,
+    tags: Vec<Tag>,
+    /// Key of the map entry whose value has not been serialized yet.
+    key: Option<String>,
+}
+
+impl CompoundSerializer {
+    fn insert<T>(&mut self, key: String, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Some(tag) = value.serialize(Serializer)? {
+            self.tags.push(named(tag, key));
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Tag>, Error> {
+        let compound = Tag::Compound(None, self.tags);
+        Ok(Some(match self.variant {
+            Some(variant) => Tag::Compound(None, vec![named(compound, variant)]),
+            None => compound,
+        }))
+    }
+}
+
+impl ser::SerializeMap for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        match key.serialize(Serializer)? {
+            Some(Tag::String(_, key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(Error::KeyMustBeString),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Custom("value serialized before its key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for CompoundSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
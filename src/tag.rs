@@ -1,7 +1,15 @@
 use crate::byte_order::ByteOrder;
+use crate::canonical;
 use crate::errors::Error;
+use crate::merge::{self, Conflict, MergeStrategy};
+use crate::patch::Patch;
+use crate::path::{NbtPath, Segment};
+#[cfg(feature = "serde")]
+use crate::serde::lossless::{parse_float, parse_long, FloatRepr, LongRepr};
+use crate::walk::{self, Walk, WalkMut};
 #[cfg(feature = "serde")]
 use serde::ser::SerializeMap;
+use std::ops::{Index, IndexMut};
 
 /// String inserted in front of nested items for pretty formatting.
 const INDENT: &str = "   "; // three spaces
@@ -125,6 +133,211 @@ impl serde::Serialize for Tag {
     }
 }
 
+/// Deserializes the representation produced by the [`serde::Serialize`]
+/// implementation of [`Tag`] as well as the one produced by
+/// [`crate::serde::Lossless`].
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::Tag;
+///
+/// let tag: Tag = serde_json::from_str(r#"{
+///     "type": "list",
+///     "name": "foo",
+///     "payload": [
+///         { "type": "byte", "name": null, "payload": 1 },
+///         { "type": "byte", "name": null, "payload": 2 }
+///     ]
+/// }"#).unwrap();
+///
+/// assert_eq!(
+///     tag,
+///     Tag::List(Some("foo".to_string()), vec![Tag::Byte(None, 1), Tag::Byte(None, 2)])
+/// );
+/// ```
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Tag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(TagVisitor)
+    }
+}
+
+/// The value of the `type` field of a serialized [`Tag`].
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TagType {
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    ByteArray,
+    String,
+    List,
+    Compound,
+    IntArray,
+    LongArray,
+}
+
+/// A payload that was encountered before the `type` field and therefore could
+/// not be deserialized into its final type right away.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum RawPayload {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Tags(Vec<Tag>),
+    Integers(Vec<LongRepr>),
+}
+
+#[cfg(feature = "serde")]
+impl RawPayload {
+    /// Converts the payload into an unnamed tag of the given type.
+    fn into_tag<E: serde::de::Error>(self, tag_type: TagType) -> Result<Tag, E> {
+        fn int<T: TryFrom<i64>, E: serde::de::Error>(value: i64) -> Result<T, E> {
+            T::try_from(value).map_err(|_| E::custom(format!("integer {} out of range", value)))
+        }
+
+        fn ints<T: TryFrom<i64>, E: serde::de::Error>(payload: RawPayload) -> Result<Vec<T>, E> {
+            match payload {
+                RawPayload::Integers(values) => values.into_iter().map(|v| int(v.0)).collect(),
+                RawPayload::Tags(tags) if tags.is_empty() => Ok(vec![]),
+                _ => Err(E::custom("expected an array of integers")),
+            }
+        }
+
+        Ok(match (tag_type, self) {
+            (TagType::Byte, RawPayload::Integer(value)) => Tag::Byte(None, int(value)?),
+            (TagType::Short, RawPayload::Integer(value)) => Tag::Short(None, int(value)?),
+            (TagType::Int, RawPayload::Integer(value)) => Tag::Int(None, int(value)?),
+            (TagType::Long, RawPayload::Integer(value)) => Tag::Long(None, value),
+            (TagType::Long, RawPayload::String(value)) => Tag::Long(None, parse_long(&value)?),
+            (TagType::Float, RawPayload::Integer(value)) => Tag::Float(None, value as f32),
+            (TagType::Float, RawPayload::Float(value)) => Tag::Float(None, value as f32),
+            (TagType::Float, RawPayload::String(value)) => {
+                Tag::Float(None, parse_float(&value)? as f32)
+            }
+            (TagType::Double, RawPayload::Integer(value)) => Tag::Double(None, value as f64),
+            (TagType::Double, RawPayload::Float(value)) => Tag::Double(None, value),
+            (TagType::Double, RawPayload::String(value)) => Tag::Double(None, parse_float(&value)?),
+            (TagType::String, RawPayload::String(value)) => Tag::String(None, value),
+            (TagType::List, RawPayload::Tags(tags)) => Tag::List(None, tags),
+            (TagType::Compound, RawPayload::Tags(tags)) => Tag::Compound(None, tags),
+            (TagType::ByteArray, payload) => Tag::ByteArray(None, ints(payload)?),
+            (TagType::IntArray, payload) => Tag::IntArray(None, ints(payload)?),
+            (TagType::LongArray, payload) => Tag::LongArray(None, ints(payload)?),
+            _ => return Err(E::custom("payload does not match the type of the tag")),
+        })
+    }
+}
+
+/// Deserializes the payload of a tag whose type is already known.
+#[cfg(feature = "serde")]
+struct PayloadSeed(TagType);
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::DeserializeSeed<'de> for PayloadSeed {
+    type Value = Tag;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::Deserialize;
+
+        Ok(match self.0 {
+            TagType::Byte => Tag::Byte(None, i8::deserialize(deserializer)?),
+            TagType::Short => Tag::Short(None, i16::deserialize(deserializer)?),
+            TagType::Int => Tag::Int(None, i32::deserialize(deserializer)?),
+            TagType::Long => Tag::Long(None, LongRepr::deserialize(deserializer)?.0),
+            TagType::Float => Tag::Float(None, FloatRepr::deserialize(deserializer)?.0 as f32),
+            TagType::Double => Tag::Double(None, FloatRepr::deserialize(deserializer)?.0),
+            TagType::ByteArray => Tag::ByteArray(None, Vec::deserialize(deserializer)?),
+            TagType::String => Tag::String(None, String::deserialize(deserializer)?),
+            TagType::List => Tag::List(None, Vec::deserialize(deserializer)?),
+            TagType::Compound => Tag::Compound(None, Vec::deserialize(deserializer)?),
+            TagType::IntArray => Tag::IntArray(None, Vec::deserialize(deserializer)?),
+            TagType::LongArray => Tag::LongArray(
+                None,
+                Vec::<LongRepr>::deserialize(deserializer)?
+                    .into_iter()
+                    .map(|long| long.0)
+                    .collect(),
+            ),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+struct TagVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map with the keys `type`, `name` and `payload`")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        use serde::de::Error;
+
+        let mut tag_type: Option<TagType> = None;
+        let mut name: Option<Name> = None;
+        let mut tag: Option<Tag> = None;
+        let mut raw: Option<RawPayload> = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "type" => {
+                    if tag_type.is_some() {
+                        return Err(A::Error::duplicate_field("type"));
+                    }
+                    tag_type = Some(map.next_value()?);
+                }
+                "name" => {
+                    if name.is_some() {
+                        return Err(A::Error::duplicate_field("name"));
+                    }
+                    name = Some(map.next_value()?);
+                }
+                "payload" => {
+                    if tag.is_some() || raw.is_some() {
+                        return Err(A::Error::duplicate_field("payload"));
+                    }
+                    match tag_type {
+                        Some(tag_type) => tag = Some(map.next_value_seed(PayloadSeed(tag_type))?),
+                        None => raw = Some(map.next_value()?),
+                    }
+                }
+                other => {
+                    return Err(A::Error::unknown_field(other, &["type", "name", "payload"]));
+                }
+            }
+        }
+
+        let tag_type = tag_type.ok_or_else(|| A::Error::missing_field("type"))?;
+        let mut tag = match (tag, raw) {
+            (Some(tag), _) => tag,
+            (None, Some(raw)) => raw.into_tag(tag_type)?,
+            (None, None) => return Err(A::Error::missing_field("payload")),
+        };
+        *tag.tag_name_mut() = name.flatten();
+        Ok(tag)
+    }
+}
+
 impl Tag {
     /// Returns a tag represented as bytes.
     pub fn to_bytes(&self, byte_order: ByteOrder) -> Result<Vec<u8>, Error> {
@@ -174,6 +387,549 @@ impl Tag {
         }
     }
 
+    /// Returns the name the tag is stored under, if any.
+    ///
+    /// Not to be confused with [Tag::name] which returns the name of the tag
+    /// type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// assert_eq!(Tag::Byte(Some("foo".to_string()), 42).tag_name(), Some("foo"));
+    /// assert_eq!(Tag::Byte(None, 42).tag_name(), None);
+    /// ```
+    pub fn tag_name(&self) -> Option<&str> {
+        match self {
+            Tag::Byte(name, _)
+            | Tag::Short(name, _)
+            | Tag::Int(name, _)
+            | Tag::Long(name, _)
+            | Tag::Float(name, _)
+            | Tag::Double(name, _)
+            | Tag::ByteArray(name, _)
+            | Tag::String(name, _)
+            | Tag::List(name, _)
+            | Tag::Compound(name, _)
+            | Tag::IntArray(name, _)
+            | Tag::LongArray(name, _) => name.as_deref(),
+        }
+    }
+
+    /// Returns a mutable reference to the name the tag is stored under.
+    pub fn tag_name_mut(&mut self) -> &mut Name {
+        match self {
+            Tag::Byte(name, _)
+            | Tag::Short(name, _)
+            | Tag::Int(name, _)
+            | Tag::Long(name, _)
+            | Tag::Float(name, _)
+            | Tag::Double(name, _)
+            | Tag::ByteArray(name, _)
+            | Tag::String(name, _)
+            | Tag::List(name, _)
+            | Tag::Compound(name, _)
+            | Tag::IntArray(name, _)
+            | Tag::LongArray(name, _) => name,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Byte`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// assert_eq!(Tag::Byte(None, 42).as_byte(), Some(42));
+    /// assert_eq!(Tag::Int(None, 42).as_byte(), None);
+    /// ```
+    pub fn as_byte(&self) -> Option<i8> {
+        match self {
+            Tag::Byte(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Short`].
+    pub fn as_short(&self) -> Option<i16> {
+        match self {
+            Tag::Short(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Int`].
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Tag::Int(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Long`].
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            Tag::Long(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Float`].
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Tag::Float(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Double`].
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            Tag::Double(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::ByteArray`].
+    pub fn as_byte_array(&self) -> Option<&[i8]> {
+        match self {
+            Tag::ByteArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::List`].
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::Compound`].
+    pub fn as_compound(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::Compound(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::IntArray`].
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        match self {
+            Tag::IntArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload if the tag is a [`Tag::LongArray`].
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Byte`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// let mut tag = Tag::Byte(None, 1);
+    /// if let Some(payload) = tag.as_byte_mut() {
+    ///     *payload += 1;
+    /// }
+    /// assert_eq!(tag, Tag::Byte(None, 2));
+    /// ```
+    pub fn as_byte_mut(&mut self) -> Option<&mut i8> {
+        match self {
+            Tag::Byte(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Short`].
+    pub fn as_short_mut(&mut self) -> Option<&mut i16> {
+        match self {
+            Tag::Short(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Int`].
+    pub fn as_int_mut(&mut self) -> Option<&mut i32> {
+        match self {
+            Tag::Int(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Long`].
+    pub fn as_long_mut(&mut self) -> Option<&mut i64> {
+        match self {
+            Tag::Long(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Float`].
+    pub fn as_float_mut(&mut self) -> Option<&mut f32> {
+        match self {
+            Tag::Float(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a [`Tag::Double`].
+    pub fn as_double_mut(&mut self) -> Option<&mut f64> {
+        match self {
+            Tag::Double(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::ByteArray`].
+    pub fn as_byte_array_mut(&mut self) -> Option<&mut Vec<i8>> {
+        match self {
+            Tag::ByteArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::String`].
+    pub fn as_string_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Tag::String(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::List`].
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Tag>> {
+        match self {
+            Tag::List(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::Compound`].
+    pub fn as_compound_mut(&mut self) -> Option<&mut Vec<Tag>> {
+        match self {
+            Tag::Compound(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::IntArray`].
+    pub fn as_int_array_mut(&mut self) -> Option<&mut Vec<i32>> {
+        match self {
+            Tag::IntArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the payload if the tag is a
+    /// [`Tag::LongArray`].
+    pub fn as_long_array_mut(&mut self) -> Option<&mut Vec<i64>> {
+        match self {
+            Tag::LongArray(_, payload) => Some(payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload of any numeric tag as an [`i64`].
+    ///
+    /// Floating point payloads are truncated towards zero and saturate at the
+    /// bounds of [`i64`]. `NaN` becomes `0`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// assert_eq!(Tag::Short(None, 42).as_i64_lossy(), Some(42));
+    /// assert_eq!(Tag::Double(None, 42.69).as_i64_lossy(), Some(42));
+    /// assert_eq!(Tag::String(None, "42".to_string()).as_i64_lossy(), None);
+    /// ```
+    pub fn as_i64_lossy(&self) -> Option<i64> {
+        match self {
+            Tag::Byte(_, payload) => Some((*payload).into()),
+            Tag::Short(_, payload) => Some((*payload).into()),
+            Tag::Int(_, payload) => Some((*payload).into()),
+            Tag::Long(_, payload) => Some(*payload),
+            Tag::Float(_, payload) => Some(*payload as i64),
+            Tag::Double(_, payload) => Some(*payload as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload of any numeric tag as an [`f64`].
+    ///
+    /// Large [`Tag::Long`] payloads may lose precision.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// assert_eq!(Tag::Int(None, 42).as_f64_lossy(), Some(42.0));
+    /// assert_eq!(Tag::Float(None, 0.5).as_f64_lossy(), Some(0.5));
+    /// ```
+    pub fn as_f64_lossy(&self) -> Option<f64> {
+        match self {
+            Tag::Byte(_, payload) => Some((*payload).into()),
+            Tag::Short(_, payload) => Some((*payload).into()),
+            Tag::Int(_, payload) => Some((*payload).into()),
+            Tag::Long(_, payload) => Some(*payload as f64),
+            Tag::Float(_, payload) => Some((*payload).into()),
+            Tag::Double(_, payload) => Some(*payload),
+            _ => None,
+        }
+    }
+
+    /// Returns the payload of a [`Tag::Byte`] as a boolean.
+    ///
+    /// Like the game, any non-zero byte is considered `true`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::Tag;
+    ///
+    /// assert_eq!(Tag::Byte(None, 1).as_bool(), Some(true));
+    /// assert_eq!(Tag::Byte(None, 0).as_bool(), Some(false));
+    /// assert_eq!(Tag::Int(None, 1).as_bool(), None);
+    /// ```
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_byte().map(|payload| payload != 0)
+    }
+
+    /// Returns the child of a [`Tag::Compound`] with the given name.
+    ///
+    /// This is [`None`] if the tag is not a compound or has no such child.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, compound, int};
+    ///
+    /// let tag = compound!(int!("Health" => 20));
+    /// assert_eq!(tag.get("Health"), Some(&Tag::Int(Some("Health".to_string()), 20)));
+    /// assert_eq!(tag.get("Hunger"), None);
+    /// ```
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.as_compound()?
+            .iter()
+            .find(|tag| tag.tag_name() == Some(name))
+    }
+
+    /// Returns a mutable reference to the child of a [`Tag::Compound`] with
+    /// the given name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Tag> {
+        self.as_compound_mut()?
+            .iter_mut()
+            .find(|tag| tag.tag_name() == Some(name))
+    }
+
+    /// Returns the element of a [`Tag::List`] at the given index.
+    ///
+    /// This is [`None`] if the tag is not a list or the index is out of
+    /// bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, byte, list};
+    ///
+    /// let tag = list!(byte!(1), byte!(2));
+    /// assert_eq!(tag.get_index(1), Some(&Tag::Byte(None, 2)));
+    /// assert_eq!(tag.get_index(2), None);
+    /// ```
+    pub fn get_index(&self, index: usize) -> Option<&Tag> {
+        self.as_list()?.get(index)
+    }
+
+    /// Returns a mutable reference to the element of a [`Tag::List`] at the
+    /// given index.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Tag> {
+        self.as_list_mut()?.get_mut(index)
+    }
+
+    /// Returns the nested tag the path points to.
+    ///
+    /// This is [`None`] if any segment of the path does not exist. Elements
+    /// of arrays are not tags and can therefore not be accessed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, compound, double, list};
+    ///
+    /// let tag = compound!(list!("Pos" => double!(0.5), double!(64.0)));
+    /// let path = "Pos[1]".parse().unwrap();
+    /// assert_eq!(tag.get_path(&path), Some(&Tag::Double(None, 64.0)));
+    /// ```
+    pub fn get_path(&self, path: &NbtPath) -> Option<&Tag> {
+        path.segments()
+            .iter()
+            .try_fold(self, |tag, segment| match segment {
+                Segment::Key(key) => tag.get(key),
+                Segment::Index(index) => tag.get_index(*index),
+            })
+    }
+
+    /// Returns a mutable reference to the nested tag the path points to.
+    pub fn get_path_mut(&mut self, path: &NbtPath) -> Option<&mut Tag> {
+        path.segments()
+            .iter()
+            .try_fold(self, |tag, segment| match segment {
+                Segment::Key(key) => tag.get_mut(key),
+                Segment::Index(index) => tag.get_index_mut(*index),
+            })
+    }
+
+    /// Applies the patch to the tag.
+    ///
+    /// See [`Patch::apply`] for details.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), Error> {
+        patch.apply(self)
+    }
+
+    /// Merges another tag into this one like the `/data merge` command.
+    ///
+    /// Children of compounds are merged recursively, lists are combined
+    /// according to the strategy and all other tags are replaced. The name of
+    /// this tag is kept. Tags replaced by a tag of a different type are
+    /// reported as conflicts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, byte, compound, int, list, string};
+    /// use mcnbt::merge::MergeStrategy;
+    ///
+    /// let mut tag = compound!(
+    ///     byte!("Count" => 1),
+    ///     list!("Items" => compound!(string!("id" => "stone"), byte!("Count" => 1))),
+    /// );
+    /// let other = compound!(
+    ///     int!("Count" => 2),
+    ///     list!("Items" => compound!(string!("id" => "stone"), byte!("Count" => 5))),
+    /// );
+    ///
+    /// let conflicts = tag.merge(&other, &MergeStrategy::MergeByKey("id".to_string()));
+    /// assert_eq!(tag["Count"], int!("Count" => 2));
+    /// assert_eq!(tag["Items"][0]["Count"].as_byte(), Some(5));
+    /// assert_eq!(conflicts.len(), 1);
+    /// assert_eq!(conflicts[0].path.to_string(), "Count");
+    /// ```
+    pub fn merge(&mut self, other: &Tag, strategy: &MergeStrategy) -> Vec<Conflict> {
+        merge::merge(self, other, strategy)
+    }
+
+    /// Visits the tag and all of its descendants depth-first.
+    ///
+    /// The closure receives the path of each tag relative to this one, its
+    /// depth and the tag itself. Its return value decides whether the
+    /// children of the tag are visited as well.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, byte, compound, list, string};
+    /// use mcnbt::walk::Walk;
+    ///
+    /// let tag = compound!(list!("Items" =>
+    ///     compound!(string!("id" => "minecraft:stone"), byte!("Count" => 1)),
+    /// ));
+    ///
+    /// let mut ids = vec![];
+    /// tag.walk(|path, depth, tag| {
+    ///     if let Some(id) = tag.as_str() {
+    ///         ids.push((path.to_string(), depth, id.to_string()));
+    ///     }
+    ///     Walk::Continue
+    /// });
+    /// assert_eq!(ids, [("Items[0].id".to_string(), 3, "minecraft:stone".to_string())]);
+    /// ```
+    pub fn walk<F>(&self, mut f: F)
+    where
+        F: FnMut(&NbtPath, usize, &Tag) -> Walk,
+    {
+        walk::walk(&mut NbtPath::new(), self, &mut f);
+    }
+
+    /// Visits the tag and all of its descendants depth-first and allows
+    /// modifying, replacing and removing them.
+    ///
+    /// Children are visited after their parent was modified. The paths of
+    /// list elements following a removed element reflect their new index.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, compound, int, string};
+    /// use mcnbt::walk::WalkMut;
+    ///
+    /// let mut tag = compound!(string!("CustomName" => "Steve"), int!("Age" => 7));
+    /// tag.walk_mut(|path, _, tag| match tag {
+    ///     Tag::String(..) => WalkMut::Remove,
+    ///     Tag::Int(_, age) => WalkMut::Replace(Tag::Short(None, *age as i16)),
+    ///     _ => WalkMut::Continue,
+    /// });
+    /// assert_eq!(tag, compound!(Tag::Short(Some("Age".to_string()), 7)));
+    /// ```
+    pub fn walk_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&NbtPath, usize, &mut Tag) -> WalkMut,
+    {
+        walk::walk_mut(&mut NbtPath::new(), self, &mut f);
+    }
+
+    /// Brings the tag and its descendants into their canonical form.
+    ///
+    /// The children of compounds are sorted by their names, all NaNs are
+    /// replaced with the same NaN and `-0.0` with `0.0`. Use
+    /// [`Canonical`](crate::canonical::Canonical) to compare and hash tags.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use mcnbt::{Tag, byte, compound};
+    ///
+    /// let mut tag = compound!(byte!("b" => 1), byte!("a" => 2));
+    /// tag.canonicalize();
+    /// assert_eq!(tag, compound!(byte!("a" => 2), byte!("b" => 1)));
+    /// ```
+    pub fn canonicalize(&mut self) {
+        canonical::canonicalize(self);
+    }
+
+    /// Returns a hash of the canonical form of the tag which is the same on
+    /// every platform.
+    ///
+    /// See [`Canonical::content_hash`](crate::canonical::Canonical::content_hash).
+    pub fn content_hash(&self) -> u64 {
+        canonical::Canonical::new(self.clone()).content_hash()
+    }
+
     /// Returns a pretty representation of the tag.
     ///
     /// See also [Tag::pretty_truncated].
@@ -501,6 +1257,206 @@ impl Tag {
     }
 }
 
+/// Accesses the child of a [`Tag::Compound`] by name.
+///
+/// # Panics
+///
+/// Panics if the tag is not a compound or has no child with the given name.
+/// Use [Tag::get] for a non-panicking alternative.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, compound, float};
+///
+/// let tag = compound!(compound!("Player" => float!("Health" => 20.0)));
+/// assert_eq!(tag["Player"]["Health"].as_float(), Some(20.0));
+/// ```
+impl Index<&str> for Tag {
+    type Output = Tag;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        match self {
+            Tag::Compound(..) => self
+                .get(name)
+                .unwrap_or_else(|| panic!("no tag named \"{}\" in TAG_Compound", name)),
+            _ => panic!("cannot index into {} with a name", self.name()),
+        }
+    }
+}
+
+impl IndexMut<&str> for Tag {
+    fn index_mut(&mut self, name: &str) -> &mut Self::Output {
+        match self {
+            Tag::Compound(..) => self
+                .get_mut(name)
+                .unwrap_or_else(|| panic!("no tag named \"{}\" in TAG_Compound", name)),
+            _ => panic!("cannot index into {} with a name", self.name()),
+        }
+    }
+}
+
+/// Accesses the element of a [`Tag::List`] by index.
+///
+/// # Panics
+///
+/// Panics if the tag is not a list or the index is out of bounds. Use
+/// [Tag::get_index] for a non-panicking alternative.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, byte, compound, list};
+///
+/// let mut tag = compound!(list!("Inventory" => compound!(byte!("Count" => 1))));
+/// tag["Inventory"][0]["Count"] = byte!("Count" => 64);
+/// assert_eq!(tag["Inventory"][0]["Count"].as_byte(), Some(64));
+/// ```
+impl Index<usize> for Tag {
+    type Output = Tag;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self {
+            Tag::List(_, payload) => &payload[index],
+            _ => panic!("cannot index into {} with an index", self.name()),
+        }
+    }
+}
+
+impl IndexMut<usize> for Tag {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self {
+            Tag::List(_, payload) => &mut payload[index],
+            _ => panic!("cannot index into {} with an index", self.name()),
+        }
+    }
+}
+
+/// Implements conversions between a payload type and an unnamed tag.
+macro_rules! impl_conversions {
+    ($variant:ident, $type:ty) => {
+        impl From<$type> for Tag {
+            fn from(value: $type) -> Self {
+                Tag::$variant(None, value)
+            }
+        }
+
+        impl TryFrom<Tag> for $type {
+            type Error = Error;
+
+            fn try_from(tag: Tag) -> Result<Self, Self::Error> {
+                match tag {
+                    Tag::$variant(_, payload) => Ok(payload),
+                    other => Err(Error::UnexpectedType {
+                        expected: Tag::$variant(None, Default::default()).name(),
+                        found: other.name(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_conversions!(Byte, i8);
+impl_conversions!(Short, i16);
+impl_conversions!(Int, i32);
+impl_conversions!(Long, i64);
+impl_conversions!(Float, f32);
+impl_conversions!(Double, f64);
+impl_conversions!(ByteArray, Vec<i8>);
+impl_conversions!(String, String);
+impl_conversions!(IntArray, Vec<i32>);
+impl_conversions!(LongArray, Vec<i64>);
+
+/// Booleans are stored as a [`Tag::Byte`] with a value of `0` or `1`.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::Tag;
+///
+/// assert_eq!(Tag::from(true), Tag::Byte(None, 1));
+/// assert!(bool::try_from(Tag::Byte(None, 1)).unwrap());
+/// assert!(i32::try_from(Tag::Byte(None, 1)).is_err());
+/// ```
+impl From<bool> for Tag {
+    fn from(value: bool) -> Self {
+        Tag::Byte(None, value.into())
+    }
+}
+
+impl TryFrom<Tag> for bool {
+    type Error = Error;
+
+    fn try_from(tag: Tag) -> Result<Self, Self::Error> {
+        tag.as_bool().ok_or(Error::UnexpectedType {
+            expected: "TAG_Byte",
+            found: tag.name(),
+        })
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(value: &str) -> Self {
+        Tag::String(None, value.to_string())
+    }
+}
+
+/// Collects tags into an unnamed [`Tag::List`]. The names of the collected
+/// tags are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::{Tag, list};
+///
+/// let tag: Tag = [1, 2, 3].into_iter().map(Tag::from).collect();
+/// assert_eq!(tag, list!(Tag::Int(None, 1), Tag::Int(None, 2), Tag::Int(None, 3)));
+/// ```
+impl FromIterator<Tag> for Tag {
+    fn from_iter<I: IntoIterator<Item = Tag>>(iter: I) -> Self {
+        Tag::List(
+            None,
+            iter.into_iter()
+                .map(|mut tag| {
+                    *tag.tag_name_mut() = None;
+                    tag
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Collects name-value pairs into an unnamed [`Tag::Compound`].
+///
+/// # Example
+///
+/// ```rust
+/// use mcnbt::Tag;
+///
+/// let tag: Tag = [
+///     ("id".to_string(), Tag::from("minecraft:stone")),
+///     ("Count".to_string(), Tag::from(1_i8)),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(tag["Count"], Tag::Byte(Some("Count".to_string()), 1));
+/// ```
+impl<T: Into<Tag>> FromIterator<(String, T)> for Tag {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        Tag::Compound(
+            None,
+            iter.into_iter()
+                .map(|(name, value)| {
+                    let mut tag = value.into();
+                    *tag.tag_name_mut() = Some(name);
+                    tag
+                })
+                .collect(),
+        )
+    }
+}
+
 /// Wraps its tags in an unnamed compound.
 ///
 /// # Example
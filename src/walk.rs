@@ -0,0 +1,211 @@
+//! Depth-first traversal of tags.
+
+use crate::path::{NbtPath, Segment};
+use crate::tag::Tag;
+
+/// What [`Tag::walk`] does after visiting a tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Walk {
+    /// Visits the children of the tag next.
+    Continue,
+    /// Does not visit the children of the tag.
+    Skip,
+    /// Ends the traversal.
+    Stop,
+}
+
+/// What [`Tag::walk_mut`] does after visiting a tag.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WalkMut {
+    /// Visits the children of the tag next.
+    Continue,
+    /// Does not visit the children of the tag.
+    Skip,
+    /// Replaces the tag but keeps its name. The children of the new tag are
+    /// not visited.
+    Replace(Tag),
+    /// Removes the tag from its parent. Removing the root has no effect.
+    Remove,
+    /// Ends the traversal.
+    Stop,
+}
+
+/// Visits the tag and its descendants and returns `true` if the traversal
+/// was stopped.
+pub(crate) fn walk<F>(path: &mut NbtPath, tag: &Tag, f: &mut F) -> bool
+where
+    F: FnMut(&NbtPath, usize, &Tag) -> Walk,
+{
+    match f(path, path.segments().len(), tag) {
+        Walk::Continue => {}
+        Walk::Skip => return false,
+        Walk::Stop => return true,
+    }
+
+    match tag {
+        Tag::List(_, elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                path.push(Segment::Index(index));
+                let stopped = walk(path, element, f);
+                path.pop();
+                if stopped {
+                    return true;
+                }
+            }
+        }
+        Tag::Compound(_, children) => {
+            for child in children {
+                path.push(Segment::Key(
+                    child.tag_name().unwrap_or_default().to_string(),
+                ));
+                let stopped = walk(path, child, f);
+                path.pop();
+                if stopped {
+                    return true;
+                }
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+/// The result of visiting a tag mutably.
+pub(crate) enum Outcome {
+    Keep,
+    Remove,
+    Stop,
+}
+
+/// Visits the tag and its descendants mutably.
+pub(crate) fn walk_mut<F>(path: &mut NbtPath, tag: &mut Tag, f: &mut F) -> Outcome
+where
+    F: FnMut(&NbtPath, usize, &mut Tag) -> WalkMut,
+{
+    match f(path, path.segments().len(), tag) {
+        WalkMut::Continue => {}
+        WalkMut::Skip => return Outcome::Keep,
+        WalkMut::Replace(mut replacement) => {
+            *replacement.tag_name_mut() = tag.tag_name_mut().take();
+            *tag = replacement;
+            return Outcome::Keep;
+        }
+        WalkMut::Remove => return Outcome::Remove,
+        WalkMut::Stop => return Outcome::Stop,
+    }
+
+    let (children, is_list) = match tag {
+        Tag::List(_, elements) => (elements, true),
+        Tag::Compound(_, children) => (children, false),
+        _ => return Outcome::Keep,
+    };
+
+    // removed children shift the indices of their following siblings
+    let mut index = 0;
+    while index < children.len() {
+        path.push(if is_list {
+            Segment::Index(index)
+        } else {
+            Segment::Key(children[index].tag_name().unwrap_or_default().to_string())
+        });
+        let outcome = walk_mut(path, &mut children[index], f);
+        path.pop();
+        match outcome {
+            Outcome::Keep => index += 1,
+            Outcome::Remove => {
+                children.remove(index);
+            }
+            Outcome::Stop => return Outcome::Stop,
+        }
+    }
+    Outcome::Keep
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{byte, compound, int, list, string};
+
+    fn player() -> Tag {
+        compound!(
+            list!("Inventory" =>
+                compound!(string!("id" => "minecraft:stone"), byte!("Count" => 1)),
+                compound!(string!("id" => "minecraft:dirt"), byte!("Count" => 2)),
+            ),
+            compound!("Abilities" => byte!("flying" => 0)),
+        )
+    }
+
+    #[test]
+    fn order() {
+        let mut visited = vec![];
+        player().walk(|path, depth, _| {
+            visited.push(format!("{}:{}", depth, path));
+            if path.to_string() == "Inventory[0]" {
+                Walk::Skip
+            } else if path.to_string() == "Abilities" {
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        });
+        assert_eq!(
+            visited,
+            [
+                "0:",
+                "1:Inventory",
+                "2:Inventory[0]",
+                "2:Inventory[1]",
+                "3:Inventory[1].id",
+                "3:Inventory[1].Count",
+                "1:Abilities",
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_and_remove() {
+        let mut tag = player();
+        let mut paths = vec![];
+        tag.walk_mut(|path, _, tag| {
+            paths.push(path.to_string());
+            match tag {
+                Tag::String(_, id) if id == "minecraft:stone" => WalkMut::Remove,
+                Tag::String(_, id) => {
+                    *id = id.replace("minecraft:", "");
+                    WalkMut::Continue
+                }
+                Tag::Byte(..) if path.to_string() == "Abilities.flying" => {
+                    WalkMut::Replace(int!(1))
+                }
+                _ => WalkMut::Continue,
+            }
+        });
+        assert_eq!(
+            tag,
+            compound!(
+                list!("Inventory" =>
+                    compound!(byte!("Count" => 1)),
+                    compound!(string!("id" => "dirt"), byte!("Count" => 2)),
+                ),
+                compound!("Abilities" => int!("flying" => 1)),
+            )
+        );
+        assert!(paths.contains(&"Inventory[0].Count".to_string()));
+    }
+
+    #[test]
+    fn remove_list_elements() {
+        let mut tag = list!(int!(1), int!(2), int!(3), int!(4));
+        let mut paths = vec![];
+        tag.walk_mut(|path, depth, tag| {
+            paths.push(path.to_string());
+            match tag.as_int() {
+                Some(value) if value % 2 == 0 && depth == 1 => WalkMut::Remove,
+                _ => WalkMut::Continue,
+            }
+        });
+        assert_eq!(tag, list!(int!(1), int!(3)));
+        assert_eq!(paths, ["", "[0]", "[1]", "[1]", "[2]"]);
+    }
+}
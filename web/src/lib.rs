@@ -1,5 +1,6 @@
 use gloo_utils::format::JsValueSerdeExt;
 use js_sys::*;
+use mcnbt::serde::Lossless;
 use mcnbt::{ByteOrder, Tag};
 use wasm_bindgen::prelude::*;
 
@@ -18,7 +19,7 @@ pub fn explore(data: Uint8Array, big_endian: bool) -> Result<JsValue, String> {
             ByteOrder::LittleEndian
         },
     ) {
-        Ok(tag) => Ok(JsValue::from_serde(&tag).unwrap()),
+        Ok(tag) => JsValue::from_serde(&Lossless(&tag)).map_err(|e| e.to_string()),
         Err(e) => Err(e.to_string()),
     }
 }